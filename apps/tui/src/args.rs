@@ -0,0 +1,437 @@
+use game_of_life::elementary::Rule1D;
+use game_of_life::grid::{EdgeBehavior, Topology};
+use game_of_life::life_history::HistoryLayer;
+use game_of_life::pattern::Pattern;
+use game_of_life::rle::PatternMeta;
+use game_of_life::symmetry::Symmetry;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum Shape {
+    #[default]
+    Rectangle,
+    // the ellipse inscribed into the board
+    Circle,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Args {
+    pub at: Option<(usize, usize)>,
+    // live cells of the initial board, in place of the default diagonal
+    pub cells: Vec<(usize, usize)>,
+    pub random: bool,
+    pub seed: Option<u64>,
+    // mirror image the random soup is drawn with, `--soup` also asks for a random board
+    pub soup: Symmetry,
+    // a new soup replaces the board when it dies out or has not changed for `reseed_after` generations
+    pub reseed_on_death: bool,
+    pub reseed_after: u64,
+    pub control: Option<String>,
+    // LifeWiki pattern fetched over the network and run, by its name in the wiki or the lexicon
+    pub pattern_name: Option<String>,
+    pub manual: bool,
+    // shows the cell under the mouse and its live neighbours in the status bar
+    pub hover: bool,
+    pub edges: EdgeBehavior,
+    pub shape: Shape,
+    // hexagonal cells with six neighbours are drawn as an offset grid
+    pub grid: Topology,
+    // steps the first row of the board as an elementary automaton instead of running Life
+    pub rule1d: Option<Rule1D>,
+    pub max_gen: Option<u64>,
+    // generations a died cell stays highlighted
+    pub fade: u32,
+    pub demo: bool,
+    // how long a paused board waits for input between redraws, off by default
+    pub idle_frame: Option<Duration>,
+    // time between two redraws, the board is stepped on its own schedule set by the speed
+    pub render_interval: Duration,
+    // placed at `at` instead of the default diagonal, read from `GOL_PATTERN` or fetched by `--wiki` and `--lexicon`
+    pub pattern: Option<Pattern>,
+    // LifeHistory states of `pattern`, relative to the same corner
+    pub life_history: Option<HistoryLayer>,
+    // name, author, comments and rules `pattern` was loaded with, kept when it is saved again
+    pub pattern_meta: PatternMeta,
+    // plain-text log of every generation
+    pub record_text: Option<PathBuf>,
+    // `generation,population,births,deaths` of every generation
+    pub log_csv: Option<PathBuf>,
+    // GIF of one period of the cycle the board settles into, recorded without a terminal
+    pub record_loop: Option<PathBuf>,
+    // pattern file to parse and describe instead of running anything
+    pub check: Option<PathBuf>,
+    // resumed on startup when it exists, and where `S` saves to
+    pub session: Option<PathBuf>,
+    // board in the `WIDTH HEIGHT` and `x y` lines format, loaded on startup when it exists and where `W` saves to
+    pub coords: Option<PathBuf>,
+    // runs `count` soups from `seed_start` without a terminal and lists the long-lived ones
+    pub soup_search: bool,
+    pub seed_start: u64,
+    pub count: u64,
+    pub min_lifespan: u64,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Args {
+            at: None,
+            cells: Vec::new(),
+            random: false,
+            seed: None,
+            soup: Symmetry::None,
+            reseed_on_death: false,
+            reseed_after: 100,
+            control: None,
+            pattern_name: None,
+            manual: false,
+            hover: true,
+            edges: EdgeBehavior::default(),
+            shape: Shape::default(),
+            grid: Topology::default(),
+            rule1d: None,
+            max_gen: None,
+            fade: 1,
+            demo: false,
+            idle_frame: None,
+            render_interval: Duration::from_millis(16),
+            pattern: None,
+            life_history: None,
+            pattern_meta: PatternMeta::default(),
+            record_text: None,
+            log_csv: None,
+            record_loop: None,
+            check: None,
+            session: None,
+            coords: None,
+            soup_search: false,
+            seed_start: 0,
+            count: 1000,
+            min_lifespan: 0,
+        }
+    }
+}
+
+impl Args {
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--at" => parsed.at = Some(parse_point(&value(&mut args, &arg)?)?),
+                "--cells" => {
+                    let cells = value(&mut args, &arg)?;
+                    parsed.cells = cells.split_whitespace().map(parse_point).collect::<Result<_, _>>()?;
+                    if parsed.cells.is_empty() {
+                        return Err("expected cells like `0,0 1,0 2,0`".to_string());
+                    }
+                }
+                "--random" => parsed.random = true,
+                "--soup" => {
+                    parsed.soup = match value(&mut args, &arg)?.as_str() {
+                        "asymmetric" => Symmetry::None,
+                        "vertical" => Symmetry::Vertical,
+                        "horizontal" => Symmetry::Horizontal,
+                        "symmetric" | "4-fold" => Symmetry::FourFold,
+                        soup => return Err(format!("expected `asymmetric`, `vertical`, `horizontal` or `symmetric`, got `{}`", soup)),
+                    };
+                    parsed.random = true;
+                }
+                "--screensaver" => {
+                    parsed.reseed_on_death = true;
+                    parsed.random = true;
+                }
+                "--reseed-after" => {
+                    let after = value(&mut args, &arg)?;
+                    parsed.reseed_after = after.parse().map_err(|_| format!("invalid generation count `{}`", after))?;
+                }
+                "--manual" => parsed.manual = true,
+                "--no-hover" => parsed.hover = false,
+                "--demo" => parsed.demo = true,
+                "--edges" | "--topology" => {
+                    parsed.edges = match value(&mut args, &arg)?.as_str() {
+                        "toroidal" => EdgeBehavior::Toroidal,
+                        "bounded" => EdgeBehavior::Bounded,
+                        "klein" => EdgeBehavior::Klein,
+                        "projective" => EdgeBehavior::Projective,
+                        edges => return Err(format!("expected `bounded`, `toroidal`, `klein` or `projective`, got `{}`", edges)),
+                    };
+                }
+                "--shape" => {
+                    parsed.shape = match value(&mut args, &arg)?.as_str() {
+                        "rectangle" => Shape::Rectangle,
+                        "circle" => Shape::Circle,
+                        shape => return Err(format!("expected `rectangle` or `circle`, got `{}`", shape)),
+                    };
+                }
+                "--grid" => {
+                    parsed.grid = match value(&mut args, &arg)?.as_str() {
+                        "square" => Topology::Square,
+                        "hex" => Topology::Hex,
+                        grid => return Err(format!("expected `square` or `hex`, got `{}`", grid)),
+                    };
+                }
+                "--rule1d" => {
+                    let rule = value(&mut args, &arg)?;
+                    parsed.rule1d = Some(Rule1D(rule.parse().map_err(|_| format!("expected a rule from 0 to 255, got `{}`", rule))?));
+                }
+                "--max-gen" => {
+                    let max_gen = value(&mut args, &arg)?;
+                    let count = max_gen.parse().ok().filter(|&count| count > 0)
+                        .ok_or_else(|| format!("invalid generation count `{}`", max_gen))?;
+                    parsed.max_gen = Some(count);
+                }
+                "--fade" => {
+                    let fade = value(&mut args, &arg)?;
+                    parsed.fade = fade.parse().map_err(|_| format!("invalid fade length `{}`", fade))?;
+                }
+                "--idle-ms" => {
+                    let idle = value(&mut args, &arg)?;
+                    let millis = idle.parse().map_err(|_| format!("invalid idle milliseconds `{}`", idle))?;
+                    parsed.idle_frame = Some(Duration::from_millis(millis));
+                }
+                "--render-ms" => {
+                    let render = value(&mut args, &arg)?;
+                    let millis = render.parse().ok().filter(|&millis| millis > 0)
+                        .ok_or_else(|| format!("invalid render milliseconds `{}`", render))?;
+                    parsed.render_interval = Duration::from_millis(millis);
+                }
+                "--record-text" => parsed.record_text = Some(PathBuf::from(value(&mut args, &arg)?)),
+                "--log-csv" => parsed.log_csv = Some(PathBuf::from(value(&mut args, &arg)?)),
+                "--record-loop" => parsed.record_loop = Some(PathBuf::from(value(&mut args, &arg)?)),
+                "--check" => parsed.check = Some(PathBuf::from(value(&mut args, &arg)?)),
+                "--session" => parsed.session = Some(PathBuf::from(value(&mut args, &arg)?)),
+                "--coords" => parsed.coords = Some(PathBuf::from(value(&mut args, &arg)?)),
+                "--soup-search" => parsed.soup_search = true,
+                "--seed-start" => {
+                    let seed = value(&mut args, &arg)?;
+                    parsed.seed_start = seed.parse().map_err(|_| format!("invalid seed `{}`", seed))?;
+                }
+                "--count" => {
+                    let count = value(&mut args, &arg)?;
+                    parsed.count = count.parse().map_err(|_| format!("invalid soup count `{}`", count))?;
+                }
+                "--min-lifespan" => {
+                    let lifespan = value(&mut args, &arg)?;
+                    parsed.min_lifespan = lifespan.parse().map_err(|_| format!("invalid lifespan `{}`", lifespan))?;
+                }
+                "--control" => parsed.control = Some(value(&mut args, &arg)?),
+                "--wiki" | "--lexicon" => parsed.pattern_name = Some(value(&mut args, &arg)?),
+                "--seed" => {
+                    let seed = value(&mut args, &arg)?;
+                    parsed.seed = Some(seed.parse().map_err(|_| format!("invalid seed `{}`", seed))?);
+                }
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+fn value(args: &mut impl Iterator<Item = String>, name: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("missing value for `{}`", name))
+}
+
+fn parse_point(value: &str) -> Result<(usize, usize), String> {
+    let (x, y) = value.split_once(',')
+        .ok_or_else(|| format!("expected `x,y`, got `{}`", value))?;
+    let x = x.trim().parse().map_err(|_| format!("invalid x coordinate `{}`", x))?;
+    let y = y.trim().parse().map_err(|_| format!("invalid y coordinate `{}`", y))?;
+    Ok((x, y))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use game_of_life::elementary::Rule1D;
+    use game_of_life::grid::{EdgeBehavior, Topology};
+    use game_of_life::symmetry::Symmetry;
+
+    use super::{Args, Shape};
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_arguments() {
+        assert_eq!(parse(&[]), Ok(Args::default()));
+    }
+
+    #[test]
+    fn at_point() {
+        assert_eq!(parse(&["--at", "10,4"]).unwrap().at, Some((10, 4)));
+        assert!(parse(&["--at", "10"]).is_err());
+        assert!(parse(&["--at", "-1,4"]).is_err());
+        assert!(parse(&["--at"]).is_err());
+    }
+
+    #[test]
+    fn cells() {
+        assert_eq!(parse(&["--cells", "0,0 1,0  2,0"]).unwrap().cells, vec![(0, 0), (1, 0), (2, 0)]);
+        assert!(parse(&[]).unwrap().cells.is_empty());
+        assert!(parse(&["--cells", "0,0 1"]).is_err());
+        assert!(parse(&["--cells", "0,-1"]).is_err());
+        assert!(parse(&["--cells", " "]).is_err());
+    }
+
+    #[test]
+    fn seed() {
+        assert_eq!(parse(&["--seed", "1234"]).unwrap().seed, Some(1234));
+        assert!(parse(&["--seed", "abc"]).is_err());
+        assert!(parse(&["--random"]).unwrap().random);
+        let args = parse(&["--soup", "symmetric"]).unwrap();
+        assert!(args.random);
+        assert_eq!(args.soup, Symmetry::FourFold);
+        assert_eq!(parse(&["--soup", "vertical"]).unwrap().soup, Symmetry::Vertical);
+        assert!(parse(&["--soup", "radial"]).is_err());
+    }
+
+    #[test]
+    fn screensaver() {
+        let args = parse(&["--screensaver", "--reseed-after", "30"]).unwrap();
+        assert!(args.reseed_on_death && args.random);
+        assert_eq!(args.reseed_after, 30);
+        assert_eq!(parse(&["--screensaver"]).unwrap().reseed_after, 100);
+        assert!(parse(&["--reseed-after", "soon"]).is_err());
+    }
+
+    #[test]
+    fn control_address() {
+        assert_eq!(parse(&["--control", "127.0.0.1:7777"]).unwrap().control.as_deref(), Some("127.0.0.1:7777"));
+    }
+
+    #[test]
+    fn pattern_name() {
+        assert_eq!(parse(&["--wiki", "Gosper glider gun"]).unwrap().pattern_name.as_deref(), Some("Gosper glider gun"));
+        assert_eq!(parse(&["--lexicon", "glider"]).unwrap().pattern_name.as_deref(), Some("glider"));
+        assert!(parse(&["--wiki"]).is_err());
+    }
+
+    #[test]
+    fn manual() {
+        assert!(parse(&["--manual"]).unwrap().manual);
+        assert!(!parse(&[]).unwrap().manual);
+        assert!(parse(&[]).unwrap().hover);
+        assert!(!parse(&["--no-hover"]).unwrap().hover);
+    }
+
+    #[test]
+    fn demo() {
+        assert!(parse(&["--demo"]).unwrap().demo);
+    }
+
+    #[test]
+    fn edges() {
+        assert_eq!(parse(&[]).unwrap().edges, EdgeBehavior::Toroidal);
+        assert_eq!(parse(&["--edges", "bounded"]).unwrap().edges, EdgeBehavior::Bounded);
+        assert_eq!(parse(&["--edges", "toroidal"]).unwrap().edges, EdgeBehavior::Toroidal);
+        assert_eq!(parse(&["--topology", "klein"]).unwrap().edges, EdgeBehavior::Klein);
+        assert_eq!(parse(&["--topology", "projective"]).unwrap().edges, EdgeBehavior::Projective);
+        assert!(parse(&["--edges", "flat"]).is_err());
+    }
+
+    #[test]
+    fn shape() {
+        assert_eq!(parse(&[]).unwrap().shape, Shape::Rectangle);
+        assert_eq!(parse(&["--shape", "circle"]).unwrap().shape, Shape::Circle);
+        assert!(parse(&["--shape", "hexagon"]).is_err());
+    }
+
+    #[test]
+    fn grid() {
+        assert_eq!(parse(&[]).unwrap().grid, Topology::Square);
+        assert_eq!(parse(&["--grid", "hex"]).unwrap().grid, Topology::Hex);
+        assert!(parse(&["--grid", "triangle"]).is_err());
+    }
+
+    #[test]
+    fn rule1d() {
+        assert_eq!(parse(&["--rule1d", "110"]).unwrap().rule1d, Some(Rule1D(110)));
+        assert!(parse(&["--rule1d", "256"]).is_err());
+        assert_eq!(parse(&[]).unwrap().rule1d, None);
+    }
+
+    #[test]
+    fn max_gen() {
+        assert_eq!(parse(&["--max-gen", "500"]).unwrap().max_gen, Some(500));
+        assert!(parse(&["--max-gen", "-1"]).is_err());
+        assert!(parse(&["--max-gen", "0"]).is_err());
+        assert!(parse(&["--max-gen"]).is_err());
+    }
+
+    #[test]
+    fn fade() {
+        assert_eq!(parse(&[]).unwrap().fade, 1);
+        assert_eq!(parse(&["--fade", "8"]).unwrap().fade, 8);
+        assert!(parse(&["--fade", "long"]).is_err());
+    }
+
+    #[test]
+    fn idle_frame() {
+        assert_eq!(parse(&[]).unwrap().idle_frame, None);
+        assert_eq!(parse(&["--idle-ms", "250"]).unwrap().idle_frame, Some(Duration::from_millis(250)));
+        assert!(parse(&["--idle-ms", "0.5"]).is_err());
+    }
+
+    #[test]
+    fn render_interval() {
+        assert_eq!(parse(&[]).unwrap().render_interval, Duration::from_millis(16));
+        assert_eq!(parse(&["--render-ms", "100"]).unwrap().render_interval, Duration::from_millis(100));
+        assert!(parse(&["--render-ms", "0"]).is_err());
+    }
+
+    #[test]
+    fn record_text() {
+        assert_eq!(parse(&["--record-text", "out.txt"]).unwrap().record_text, Some("out.txt".into()));
+        assert!(parse(&["--record-text"]).is_err());
+    }
+
+    #[test]
+    fn session() {
+        assert_eq!(parse(&["--session", "life.json"]).unwrap().session, Some("life.json".into()));
+        assert!(parse(&["--session"]).is_err());
+    }
+
+    #[test]
+    fn coords() {
+        assert_eq!(parse(&["--coords", "seed.txt"]).unwrap().coords, Some(PathBuf::from("seed.txt")));
+        assert!(parse(&["--coords"]).is_err());
+    }
+
+    #[test]
+    fn log_csv() {
+        assert_eq!(parse(&["--log-csv", "out.csv"]).unwrap().log_csv, Some(PathBuf::from("out.csv")));
+        assert!(parse(&["--log-csv"]).is_err());
+    }
+
+    #[test]
+    fn record_loop() {
+        assert_eq!(parse(&["--record-loop", "out.gif"]).unwrap().record_loop, Some(PathBuf::from("out.gif")));
+        assert!(parse(&["--record-loop"]).is_err());
+    }
+
+    #[test]
+    fn check() {
+        assert_eq!(parse(&["--check", "glider.rle"]).unwrap().check, Some(PathBuf::from("glider.rle")));
+        assert!(parse(&["--check"]).is_err());
+    }
+
+    #[test]
+    fn soup_search() {
+        let args = parse(&["--soup-search", "--seed-start", "10", "--count", "50", "--min-lifespan", "500"]).unwrap();
+        assert!(args.soup_search);
+        assert_eq!((args.seed_start, args.count, args.min_lifespan), (10, 50, 500));
+        assert_eq!(parse(&[]).unwrap().count, 1000);
+        assert!(parse(&["--count", "many"]).is_err());
+    }
+
+    #[test]
+    fn unknown_argument() {
+        assert!(parse(&["--nope"]).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use std::io::{BufRead, BufReader, Result, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use crate::tui::BoardEvent;
+
+/// Accepts line based commands on `address` and forwards them as `BoardEvent`s:
+/// `step`, `pause`, `clear`, `set x y`, `rule B3/S23` and `get` (replies with RLE).
+pub fn listen(address: &str, sender: Sender<BoardEvent>) -> Result<()> {
+    let listener = TcpListener::bind(address)?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let sender = sender.clone();
+            thread::spawn(move || serve(stream, sender));
+        }
+    });
+    Ok(())
+}
+
+fn serve(stream: TcpStream, sender: Sender<BoardEvent>) -> Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (reply, replies) = mpsc::channel();
+        let response = match parse_command(&line, reply) {
+            Ok(event) => {
+                let awaits_reply = matches!(event, BoardEvent::Get(_));
+                if sender.send(event).is_err() {
+                    break;
+                }
+                if awaits_reply {
+                    replies.recv().unwrap_or_default()
+                } else {
+                    "ok".to_string()
+                }
+            }
+            Err(err) => format!("error: {}", err),
+        };
+        writeln!(writer, "{}", response.trim_end())?;
+    }
+    Ok(())
+}
+
+fn parse_command(line: &str, reply: Sender<String>) -> std::result::Result<BoardEvent, String> {
+    let mut words = line.split_whitespace();
+    let command = words.next().unwrap_or_default();
+    let event = match command {
+        "step" => BoardEvent::Step,
+        "pause" => BoardEvent::Pause,
+        "clear" => BoardEvent::Clear,
+        "get" => BoardEvent::Get(reply),
+        "set" => {
+            let mut coordinate = |name| words.next()
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| format!("expected `set x y`, missing or invalid {}", name));
+            let x = coordinate("x")?;
+            let y = coordinate("y")?;
+            BoardEvent::Set { x, y }
+        }
+        "rule" => {
+            let rules = words.next().ok_or("expected `rule B3/S23`")?;
+            BoardEvent::Rules(rules.parse().map_err(|err| format!("{}", err))?)
+        }
+        _ => return Err(format!("unknown command `{}`", command)),
+    };
+    match words.next() {
+        Some(extra) => Err(format!("unexpected `{}`", extra)),
+        None => Ok(event),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use game_of_life::rules::Rules;
+
+    use crate::tui::BoardEvent;
+
+    use super::parse_command;
+
+    fn parse(line: &str) -> Result<BoardEvent, String> {
+        parse_command(line, mpsc::channel().0)
+    }
+
+    #[test]
+    fn commands() {
+        assert!(matches!(parse("step"), Ok(BoardEvent::Step)));
+        assert!(matches!(parse(" pause "), Ok(BoardEvent::Pause)));
+        assert!(matches!(parse("clear"), Ok(BoardEvent::Clear)));
+        assert!(matches!(parse("get"), Ok(BoardEvent::Get(_))));
+        assert!(matches!(parse("set 3 4"), Ok(BoardEvent::Set { x: 3, y: 4 })));
+        assert!(matches!(parse("rule B36/S23"), Ok(BoardEvent::Rules(rules)) if rules == Rules::highlife()));
+    }
+
+    #[test]
+    fn invalid_commands() {
+        assert!(parse("jump").is_err());
+        assert!(parse("set 3").is_err());
+        assert!(parse("set 3 -4").is_err());
+        assert!(parse("rule 3").is_err());
+        assert!(parse("step 2").is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use game_of_life::life_history::{from_history_rle, is_history_rle};
+use game_of_life::rle::from_rle;
+
+// where LifeWiki keeps the RLE of its patterns
+const DEFAULT_URL: &str = "https://conwaylife.com/patterns/";
+// overrides `DEFAULT_URL`, e.g. with a mirror
+const URL_VAR: &str = "GOL_PATTERN_URL";
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// File LifeWiki stores the pattern `name` under, its letters and digits in lower case.
+pub fn pattern_file(name: &str) -> Option<String> {
+    let stem: String = name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    Some(format!("{}.rle", stem)).filter(|_| !stem.is_empty())
+}
+
+/// RLE of the named pattern, fetched from the pattern collection unless an earlier fetch
+/// left it in the cache.
+pub fn fetch_pattern(name: &str) -> Result<String, String> {
+    let url = std::env::var(URL_VAR).unwrap_or_else(|_| DEFAULT_URL.to_string());
+    fetch_into(name, &url, &cache_dir())
+}
+
+fn fetch_into(name: &str, url: &str, cache: &Path) -> Result<String, String> {
+    let file = pattern_file(name).ok_or_else(|| format!("`{}` is no pattern name", name))?;
+    let cached = cache.join(&file);
+    if let Ok(rle) = std::fs::read_to_string(&cached) {
+        return Ok(rle);
+    }
+    let rle = http_get(&format!("{}/{}", url.trim_end_matches('/'), file))
+        .map_err(|err| format!("cannot fetch `{}`: {}", name, err))?;
+    // anything else, such as an error page, is left for the caller to report and fetched again next time
+    let parses = if is_history_rle(&rle) { from_history_rle(&rle).is_ok() } else { from_rle(&rle).is_ok() };
+    if parses {
+        // a pattern that cannot be cached is still worth running
+        let _ = std::fs::create_dir_all(cache).and_then(|()| std::fs::write(&cached, &rle));
+    }
+    Ok(rle)
+}
+
+fn cache_dir() -> PathBuf {
+    let base = match (std::env::var_os("XDG_CACHE_HOME"), std::env::var_os("HOME")) {
+        (Some(cache), _) => PathBuf::from(cache),
+        (None, Some(home)) => PathBuf::from(home).join(".cache"),
+        (None, None) => std::env::temp_dir(),
+    };
+    base.join("game_of_life").join("patterns")
+}
+
+// follows redirects, such as the one from http:// to https:// on LifeWiki
+fn http_get(url: &str) -> Result<String, String> {
+    let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+    match agent.get(url).call() {
+        Ok(response) => response.into_string().map_err(|err| err.to_string()),
+        Err(ureq::Error::Status(404, _)) => Err("no such pattern".to_string()),
+        Err(ureq::Error::Status(status, _)) => Err(format!("server answered {}", status)),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+    use std::thread::{self, JoinHandle};
+
+    use super::{fetch_into, pattern_file};
+
+    // answers a connection with each of `responses` in turn, then returns the request lines
+    fn serve(listener: TcpListener, responses: Vec<String>) -> JoinHandle<Vec<String>> {
+        thread::spawn(move || {
+            responses.iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request = [0; 1024];
+                    let read = stream.read(&mut request).unwrap();
+                    stream.write_all(response.as_bytes()).unwrap();
+                    String::from_utf8_lossy(&request[..read]).lines().next().unwrap_or_default().to_string()
+                })
+                .collect()
+        })
+    }
+
+    fn cache(test: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("gol-fetch-{}-{}", test, std::process::id()))
+    }
+
+    #[test]
+    fn pattern_files() {
+        assert_eq!(pattern_file("Gosper glider gun").as_deref(), Some("gosperglidergun.rle"));
+        assert_eq!(pattern_file("glider").as_deref(), Some("glider.rle"));
+        assert_eq!(pattern_file(" / "), None);
+    }
+
+    #[test]
+    fn fetched_patterns_are_cached() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let url = format!("http://{}/patterns/", address);
+        // serves a redirect and the pattern once, so the second fetch has to come from the cache
+        let server = serve(listener, vec![
+            format!("HTTP/1.1 301 Moved Permanently\r\nLocation: http://{}/moved/blinker.rle\r\nContent-Length: 0\r\n\r\n", address),
+            "HTTP/1.0 200 OK\r\n\r\nx = 3, y = 1\n3o!\n".to_string(),
+        ]);
+        let cache = cache("cached");
+        assert_eq!(fetch_into("Blinker", &url, &cache).unwrap(), "x = 3, y = 1\n3o!\n");
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("GET /patterns/blinker.rle "));
+        assert!(requests[1].starts_with("GET /moved/blinker.rle "));
+        assert_eq!(fetch_into("blinker", &url, &cache).unwrap(), "x = 3, y = 1\n3o!\n");
+        std::fs::remove_dir_all(cache).unwrap();
+    }
+
+    #[test]
+    fn only_patterns_are_cached() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/patterns/", listener.local_addr().unwrap());
+        let page = "HTTP/1.0 200 OK\r\n\r\n<html>maintenance</html>".to_string();
+        let server = serve(listener, vec![page.clone(), page, "HTTP/1.0 404 Not Found\r\n\r\n".to_string()]);
+        let cache = cache("uncached");
+        assert_eq!(fetch_into("glider", &url, &cache).unwrap(), "<html>maintenance</html>");
+        assert_eq!(fetch_into("glider", &url, &cache).unwrap(), "<html>maintenance</html>");
+        assert_eq!(fetch_into("glider", &url, &cache), Err("cannot fetch `glider`: no such pattern".to_string()));
+        assert_eq!(server.join().unwrap().len(), 3);
+        assert!(!cache.join("glider.rle").exists());
+    }
+}
@@ -1,18 +1,217 @@
-use crate::tui::main_loop;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use game_of_life::board::Board;
+use game_of_life::gif::to_gif;
+use game_of_life::grid::Topology;
+use game_of_life::life_history::{from_history_rle, is_history_rle};
+use game_of_life::logic::{detect_cycle, Generations};
+use game_of_life::pattern::{Pattern, PatternError};
+use game_of_life::rle::{from_rle, is_rle, read_meta};
+use game_of_life::rules::Rules;
+use game_of_life::soup::SoupSearch;
+
+use crate::args::Args;
+use crate::tui::{cleanup_terminal, initial_board, initial_board_size, main_loop};
+
+mod args;
+#[cfg(feature = "net")]
+mod control;
+#[cfg(feature = "net")]
+mod fetch;
+mod render;
 mod tui;
 
+const PATTERN_VAR: &str = "GOL_PATTERN";
+// how long `--record-loop` looks for a cycle without `--max-gen`, and the longest period it records
+const LOOP_SEARCH_GENERATIONS: u64 = 2000;
+const LOOP_MAX_PERIOD: usize = 256;
+// the default speed of the terminal view
+const LOOP_FRAME_DELAY: Duration = Duration::from_millis(64);
+const LOOP_CELL_PIXELS: usize = 4;
 
 fn main() -> std::io::Result<()> {
-    main_loop()
-        .map_err(|err| {
-            match crossterm::terminal::disable_raw_mode() {
-                Ok(_) => err,
-                Err(e) => e,
+    let mut args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::exit(2);
+        }
+    };
+    if cfg!(not(feature = "net")) && args.control.is_some() {
+        eprintln!("error: `--control` requires building with the `net` feature");
+        std::process::exit(2);
+    }
+    if cfg!(not(feature = "net")) && args.pattern_name.is_some() {
+        eprintln!("error: `--wiki` and `--lexicon` require building with the `net` feature");
+        std::process::exit(2);
+    }
+    if cfg!(not(feature = "serde")) && args.session.is_some() {
+        eprintln!("error: `--session` requires building with the `serde` feature");
+        std::process::exit(2);
+    }
+    if args.rule1d.is_some() && (args.record_loop.is_some() || args.soup_search) {
+        eprintln!("error: `--rule1d` only runs in the terminal view");
+        std::process::exit(2);
+    }
+    if let Some(path) = &args.check {
+        if let Err(err) = check_pattern(path, args.grid) {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    #[cfg(feature = "net")]
+    if let Some(name) = args.pattern_name.clone() {
+        let rle = fetch::fetch_pattern(&name).unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        });
+        if let Err(err) = load_pattern(&rle, &mut args) {
+            eprintln!("error: invalid pattern `{}`: {}", name, err);
+            std::process::exit(2);
+        }
+    }
+    // flags choosing the initial board take precedence over the environment
+    if args.pattern.is_none() && !args.random && args.seed.is_none() && !args.demo && args.cells.is_empty() {
+        if let Ok(rle) = std::env::var(PATTERN_VAR) {
+            if let Err(err) = load_pattern(&rle, &mut args) {
+                eprintln!("error: invalid pattern in `{}`: {}", PATTERN_VAR, err);
+                std::process::exit(2);
             }
-        })
+        }
+    }
+    if args.soup_search {
+        soup_search(&args);
+        return Ok(());
+    }
+    let (width, height) = initial_board_size(args.grid);
+    if let Some((x, y)) = args.cells.iter().find(|&&(x, y)| x >= width || y >= height) {
+        eprintln!("error: cell ({}, {}) is outside of the {}x{} board", x, y, width, height);
+        std::process::exit(2);
+    }
+    if args.random && args.seed.is_none() {
+        args.seed = Some(random_seed());
+    }
+    if let Some(path) = &args.record_loop {
+        // the terminal view names the seed in its summary on exit instead
+        if let Some(seed) = args.seed {
+            println!("seed: {}", seed);
+        }
+        if let Err(err) = record_loop(path, &args) {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    // a panic message printed in raw mode with mouse capture on would leave the shell unusable
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = cleanup_terminal();
+        default_hook(info);
+    }));
+    let stats = main_loop(args)
+        .inspect_err(|_| {
+            let _ = cleanup_terminal();
+        })?;
+    println!("{}", stats);
+    Ok(())
+}
+
+fn load_pattern(rle: &str, args: &mut Args) -> Result<(), PatternError> {
+    if is_history_rle(rle) {
+        let layer = from_history_rle(rle)?;
+        args.pattern = Some(layer.pattern());
+        args.life_history = Some(layer);
+    } else {
+        args.pattern = Some(from_rle(rle)?);
+    }
+    args.pattern_meta = read_meta(rle);
+    Ok(())
+}
+
+// describes the pattern in `path` as far as the parsers get, for scripts and CI
+fn check_pattern(path: &Path, topology: Topology) -> Result<(), String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("cannot read `{}`: {}", path.display(), err))?;
+    let (format, pattern) = parse_any(&text);
+    println!("format: {}", format);
+    if let Some(name) = read_meta(&text).name {
+        println!("name: {}", name);
+    }
+    let pattern = pattern.map_err(|err| format!("invalid pattern in `{}`: {}", path.display(), err))?;
+    let (width, height) = initial_board_size(topology);
+    println!("size: {}x{}", pattern.width(), pattern.height());
+    println!("population: {}", pattern.cells().len());
+    if pattern.width() <= width && pattern.height() <= height {
+        println!("fits: yes");
+    } else {
+        println!("fits: no, the terminal shows {}x{} cells", width, height);
+    }
+    Ok(())
 }
 
+fn parse_any(text: &str) -> (&'static str, Result<Pattern, PatternError>) {
+    if text.trim_start().starts_with("#Life 1.06") {
+        let board = Board::from_life106(text);
+        let pattern = board.map(|board| Pattern::from_cells(board.iter().filter(|entry| entry.cell().is_alive()).map(|entry| entry.index())));
+        ("Life 1.06", pattern)
+    } else if is_history_rle(text) {
+        ("LifeHistory RLE", from_history_rle(text).map(|layer| layer.pattern()))
+    } else if is_rle(text) {
+        ("RLE", from_rle(text))
+    } else {
+        ("plaintext", Pattern::from_plaintext(text))
+    }
+}
+
+// one period of the cycle the board settles into, so the GIF loops without a jump,
+// or the first `--max-gen` generations when there is none
+fn record_loop(path: &Path, args: &Args) -> Result<(), String> {
+    let (board, _) = initial_board(args);
+    let rules = args.pattern_meta.rules.unwrap_or_else(Rules::conway);
+    let search = args.max_gen.unwrap_or(LOOP_SEARCH_GENERATIONS);
+    let (start, length) = match detect_cycle(&mut board.clone(), &rules, LOOP_MAX_PERIOD, search) {
+        Some(cycle) => (cycle.start, cycle.period),
+        None if args.max_gen.is_some() => (0, search),
+        None => return Err(format!("no cycle within {} generations, `--max-gen` records them anyway", search)),
+    };
+    let frames: Vec<_> = std::iter::once(board.clone())
+        .chain(Generations::new(board, rules))
+        .skip(start as usize)
+        .take(length as usize)
+        .collect();
+    let gif = to_gif(&frames, LOOP_CELL_PIXELS, LOOP_FRAME_DELAY).ok_or("the board is too large for a GIF")?;
+    std::fs::write(path, gif).map_err(|err| format!("cannot write `{}`: {}", path.display(), err))?;
+    println!("recorded generations {} to {} into {}", start, start + length - 1, path.display());
+    Ok(())
+}
 
+fn soup_search(args: &Args) {
+    let search = SoupSearch {
+        max_gen: args.max_gen.unwrap_or(SoupSearch::default().max_gen),
+        min_lifespan: args.min_lifespan,
+        ..SoupSearch::default()
+    };
+    println!("seed\tlifespan\tfinal population");
+    for result in search.run(args.seed_start..args.seed_start.saturating_add(args.count)) {
+        println!("{}\t{}\t{}", result.seed, result.lifespan, result.final_population);
+    }
+}
 
+// the clock only stands in where there is no /dev/urandom
+fn random_seed() -> u64 {
+    let mut bytes = [0; 8];
+    match File::open("/dev/urandom").and_then(|mut urandom| urandom.read_exact(&mut bytes)) {
+        Ok(()) => u64::from_le_bytes(bytes),
+        Err(_) => clock_seed(),
+    }
+}
 
+fn clock_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or_default()
+}
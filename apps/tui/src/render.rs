@@ -0,0 +1,94 @@
+use std::io::{stdout, Stdout};
+
+use crossterm::Result;
+
+use game_of_life::board::Board;
+use game_of_life::life_history::HistoryLayer;
+use game_of_life::mask::Mask;
+
+use crate::tui::{draw_board, draw_minimap, draw_offscreen_hints, Area, Theme};
+
+/// How a board is to be drawn, besides its cells.
+#[derive(Debug, Copy, Clone)]
+pub struct Frame<'a> {
+    pub theme: &'a Theme,
+    // generations each cell has spent in its state, row-major like the board
+    pub ages: &'a [u32],
+    pub age_colors: bool,
+    // cells that have ever been alive, row-major like the board, only in trace mode
+    pub trails: Option<&'a [bool]>,
+    pub minimap: bool,
+    pub mask: Option<&'a Mask>,
+    pub history: Option<&'a HistoryLayer>,
+    pub area: Area,
+}
+
+/// Backend the main loop draws boards with, so it does not depend on where they end up.
+pub trait Renderer {
+    fn draw(&mut self, board: &Board, frame: &Frame<'_>) -> Result<()>;
+}
+
+/// Draws into the terminal through crossterm.
+pub struct TerminalRenderer {
+    stdout: Stdout,
+}
+
+impl TerminalRenderer {
+    pub fn new() -> Self {
+        TerminalRenderer { stdout: stdout() }
+    }
+}
+
+impl Renderer for TerminalRenderer {
+    fn draw(&mut self, board: &Board, frame: &Frame<'_>) -> Result<()> {
+        draw_board(frame.theme, &mut self.stdout, board, frame.ages, frame.age_colors, frame.trails, frame.mask, frame.history, frame.area)?;
+        draw_offscreen_hints(frame.theme, &mut self.stdout, board, frame.area)?;
+        if frame.minimap {
+            draw_minimap(frame.theme, &mut self.stdout, board, frame.area)?;
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use game_of_life::board::Board;
+
+    use crate::tui::{Area, DEFAULT_THEME};
+
+    use super::{Frame, Renderer};
+
+    // captures what would be drawn instead of drawing it
+    #[derive(Default)]
+    struct Recorder {
+        drawn: Vec<(String, Area)>,
+    }
+
+    impl Renderer for Recorder {
+        fn draw(&mut self, board: &Board, frame: &Frame<'_>) -> crossterm::Result<()> {
+            self.drawn.push((board.to_string(), frame.area));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn boxed_renderers_receive_frames() {
+        let board = Board::from_live_coords(2, 1, [(1, 0)]).unwrap();
+        let area = Area::of(&board);
+        let frame = Frame {
+            theme: &DEFAULT_THEME,
+            ages: &[0, 0],
+            age_colors: false,
+            trails: None,
+            minimap: false,
+            mask: None,
+            history: None,
+            area,
+        };
+        let mut recorder = Recorder::default();
+        let renderer: &mut dyn Renderer = &mut recorder;
+        renderer.draw(&board, &frame).unwrap();
+        assert_eq!(recorder.drawn, [(board.to_string(), area)]);
+    }
+}
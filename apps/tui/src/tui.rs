@@ -1,34 +1,72 @@
-use std::cmp::min;
-use std::io::{Stdout, Write};
-use std::io::stdout;
+use std::cmp::{max, min};
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, IsTerminal, Stdout, Write};
+use std::io::{stdin, stdout};
 use std::ops::IndexMut;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
 use std::time::{Duration, Instant};
 
 use crossterm::{cursor, QueueableCommand, Result, style};
-use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
-use crossterm::event::{Event, KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind, poll, read};
+use crossterm::event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind, read};
 use crossterm::ExecutableCommand;
-use crossterm::style::{ContentStyle, StyledContent, Stylize};
+use crossterm::style::{Color, ContentStyle, StyledContent, Stylize};
 use crossterm::terminal::{Clear, ClearType};
 use crossterm::terminal;
 use once_cell::sync::Lazy;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use game_of_life::board::{Board, Cell};
-use game_of_life::logic::{next_state, resize};
+use game_of_life::elementary::{step_1d, Rule1D};
+use game_of_life::grid::{EdgeBehavior, Topology};
+use game_of_life::history::History;
+use game_of_life::life_history::{HistoryLayer, HistoryState};
+use game_of_life::logic::{classify_motion, Cycle, live_neighbours, Motion, next_state_masked, next_state_with, resize};
+use game_of_life::mask::Mask;
+use game_of_life::pattern::Pattern;
+use game_of_life::rle::{to_rle_with_meta, PatternMeta};
+use game_of_life::rules::Rules;
+#[cfg(feature = "serde")]
+use game_of_life::session::Session;
+use game_of_life::symmetry::Symmetry;
 
-#[derive(Debug)]
+use crate::args::{Args, Shape};
+use crate::render::{Frame, Renderer, TerminalRenderer};
+
+#[derive(Clone, Debug)]
 pub struct Theme {
     dead_cell_style: ContentStyle,
     alive_cell_style: ContentStyle,
     died_cell_style: ContentStyle,
     born_cell_style: ContentStyle,
+    wall_cell_style: ContentStyle,
+    // dead cells that were alive at some point, drawn with the alive glyph in trace mode
+    trail_cell_style: ContentStyle,
     dead_cell_content: String,
     alive_cell_content: String,
     died_cell_content: String,
     born_cell_content: String,
+    age_colors: Vec<Color>,
+    fade_colors: Vec<Color>,
+    // LifeHistory states from ghost to boundary
+    history_colors: Vec<Color>,
+    // terminal columns each cell takes, 2 for wide glyphs
+    cell_columns: u16,
+    // dead cells are unstyled spaces, which is far less output for sparse boards
+    blank_dead: bool,
+    // live cells are colored by age even with age colors switched off
+    heat: bool,
 }
 
 impl Theme {
+    /// Fails unless every glyph takes the same number of terminal columns, by their Unicode width.
+    /// Two columns are allowed on purpose, so that CJK characters or emoji can be cells: the board
+    /// is then drawn with every cell two columns wide, which also squares up the cells.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         dead_cell_style: ContentStyle,
         alive_cell_style: ContentStyle,
@@ -38,8 +76,18 @@ impl Theme {
         alive_cell_content: String,
         died_cell_content: String,
         born_cell_content: String,
-    ) -> Self {
-        Self {
+    ) -> std::result::Result<Self, String> {
+        let cell_columns = alive_cell_content.width();
+        if !(1..=2).contains(&cell_columns) {
+            return Err(format!("`{}` takes {} columns, cells are 1 or 2 columns wide", alive_cell_content, cell_columns));
+        }
+        for glyph in [&dead_cell_content, &died_cell_content, &born_cell_content] {
+            let width = glyph.width();
+            if width != cell_columns {
+                return Err(format!("`{}` takes {} columns but `{}` takes {}", glyph, width, alive_cell_content, cell_columns));
+            }
+        }
+        Ok(Self {
             dead_cell_style,
             alive_cell_style,
             died_cell_style,
@@ -48,10 +96,216 @@ impl Theme {
             alive_cell_content,
             died_cell_content,
             born_cell_content,
+            wall_cell_style: dead_cell_style,
+            trail_cell_style: ContentStyle::new().dark_blue(),
+            age_colors: Vec::new(),
+            fade_colors: Vec::new(),
+            history_colors: Vec::new(),
+            cell_columns: cell_columns as u16,
+            blank_dead: false,
+            heat: false,
+        })
+    }
+
+    pub fn cell_columns(&self) -> u16 { self.cell_columns }
+
+    /// Style of dead cells along the edges of bounded boards.
+    pub fn with_wall_style(self, wall_cell_style: ContentStyle) -> Theme {
+        Theme { wall_cell_style, ..self }
+    }
+
+    /// Colors for live cells by age, youngest first, the last one is used for all older cells.
+    pub fn with_age_colors(self, age_colors: Vec<Color>) -> Theme {
+        Theme { age_colors, ..self }
+    }
+
+    /// Colors for the LifeHistory states other than off and on, in the order of their RLE tags `BCDEF`.
+    pub fn with_history_colors(self, history_colors: Vec<Color>) -> Theme {
+        Theme { history_colors, ..self }
+    }
+
+    /// Draws settled dead cells as plain spaces instead of the dead glyph.
+    pub fn with_blank_dead(self, blank_dead: bool) -> Theme {
+        Theme { blank_dead, ..self }
+    }
+
+    /// Always colors live cells by age, a heatmap of how long they have been alive.
+    pub fn with_heat(self, heat: bool) -> Theme {
+        Theme { heat, ..self }
+    }
+
+    /// Copy of the theme without born and died highlights, only alive and dead cells.
+    pub fn plain(&self) -> Theme {
+        Theme {
+            born_cell_style: self.alive_cell_style,
+            born_cell_content: self.alive_cell_content.clone(),
+            died_cell_style: self.dead_cell_style,
+            died_cell_content: self.dead_cell_content.clone(),
+            fade_colors: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Keeps cells that died highlighted for `generations`, fading from the died to the dead color.
+    pub fn with_fade(self, generations: u32) -> Theme {
+        let rgb = |style: ContentStyle| style.foreground_color.and_then(color_to_rgb);
+        let fade_colors = match (rgb(self.died_cell_style), rgb(self.dead_cell_style)) {
+            (Some((r0, g0, b0)), Some((r1, g1, b1))) => (0..generations)
+                .map(|age| {
+                    let t = age as f32 / generations as f32;
+                    let mix = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * t).round() as u8;
+                    Color::Rgb { r: mix(r0, r1), g: mix(g0, g1), b: mix(b0, b1) }
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        Theme { fade_colors, ..self }
+    }
+
+    fn fade_style(&self, age: u32) -> Option<ContentStyle> {
+        let color = self.fade_colors.get(age as usize)?;
+        Some(self.died_cell_style.with(*color))
+    }
+
+    fn history_style(&self, state: HistoryState) -> Option<StyledContent<&str>> {
+        let color = match state {
+            HistoryState::Off | HistoryState::On => return None,
+            state => self.history_colors.get(state as usize - HistoryState::Ghost as usize)?,
+        };
+        let styled = if state.is_alive() {
+            StyledContent::new(self.alive_cell_style.with(*color), self.alive_cell_content.as_str())
+        } else {
+            StyledContent::new(self.dead_cell_style.with(*color), self.dead_cell_content.as_str())
+        };
+        Some(styled)
+    }
+
+    fn age_style(&self, age: u32) -> ContentStyle {
+        let index = min((age / AGE_STEP) as usize, self.age_colors.len().saturating_sub(1));
+        match self.age_colors.get(index) {
+            Some(&color) => self.alive_cell_style.with(color),
+            None => self.alive_cell_style,
+        }
+    }
+
+    /// Copy of the theme with the alive color rotated around the hue wheel, `t` in turns.
+    pub fn hue_shifted(&self, t: f32) -> Theme {
+        let (r, g, b) = hsv_to_rgb(t, 1.0, 1.0);
+        Theme {
+            alive_cell_style: self.alive_cell_style.with(Color::Rgb { r, g, b }),
+            ..self.clone()
+        }
+    }
+
+    /// Copy of the theme with every RGB color replaced by the closest basic ANSI color.
+    pub fn degrade_to_ansi16(&self) -> Theme {
+        let degrade = |style: ContentStyle| ContentStyle {
+            foreground_color: style.foreground_color.map(nearest_ansi16),
+            background_color: style.background_color.map(nearest_ansi16),
+            underline_color: style.underline_color.map(nearest_ansi16),
+            ..style
+        };
+        Theme {
+            dead_cell_style: degrade(self.dead_cell_style),
+            alive_cell_style: degrade(self.alive_cell_style),
+            died_cell_style: degrade(self.died_cell_style),
+            born_cell_style: degrade(self.born_cell_style),
+            wall_cell_style: degrade(self.wall_cell_style),
+            trail_cell_style: degrade(self.trail_cell_style),
+            age_colors: self.age_colors.iter().copied().map(nearest_ansi16).collect(),
+            fade_colors: self.fade_colors.iter().copied().map(nearest_ansi16).collect(),
+            history_colors: self.history_colors.iter().copied().map(nearest_ansi16).collect(),
+            ..self.clone()
+        }
+    }
+
+    pub fn for_support(&self, support: ColorSupport) -> Theme {
+        match support {
+            ColorSupport::TrueColor => self.clone(),
+            ColorSupport::Ansi16 => self.degrade_to_ansi16(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ColorSupport {
+    Ansi16,
+    TrueColor,
+}
+
+impl ColorSupport {
+    pub fn detect() -> Self {
+        Self::from_colorterm(std::env::var("COLORTERM").ok().as_deref())
+    }
+
+    fn from_colorterm(colorterm: Option<&str>) -> Self {
+        match colorterm {
+            Some("truecolor" | "24bit") => ColorSupport::TrueColor,
+            _ => ColorSupport::Ansi16,
         }
     }
 }
 
+pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (u8, u8, u8) {
+    let h = h.rem_euclid(1.0) * 6.0;
+    let c = v * s;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u8 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = v - c;
+    let channel = |value: f32| ((value + m) * 255.0).round() as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+const ANSI_16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (128, 0, 0)),
+    (Color::DarkGreen, (0, 128, 0)),
+    (Color::DarkYellow, (128, 128, 0)),
+    (Color::DarkBlue, (0, 0, 128)),
+    (Color::DarkMagenta, (128, 0, 128)),
+    (Color::DarkCyan, (0, 128, 128)),
+    (Color::Grey, (192, 192, 192)),
+    (Color::DarkGrey, (128, 128, 128)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (0, 0, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+fn color_to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb { r, g, b } => Some((r, g, b)),
+        _ => ANSI_16.iter().find(|(ansi, _)| *ansi == color).map(|(_, rgb)| *rgb),
+    }
+}
+
+/// Closest of the 16 basic ANSI colors, for terminals without truecolor.
+pub fn nearest_ansi16(color: Color) -> Color {
+    let Color::Rgb { r, g, b } = color else {
+        return color;
+    };
+    let distance = |(ar, ag, ab): (u8, u8, u8)| {
+        let dr = ar as i32 - r as i32;
+        let dg = ag as i32 - g as i32;
+        let db = ab as i32 - b as i32;
+        dr * dr + dg * dg + db * db
+    };
+    ANSI_16.iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map(|(ansi, _)| *ansi)
+        .unwrap_or(color)
+}
+
 pub static DEFAULT_THEME: Lazy<Theme> = Lazy::new(|| {
     Theme::new(
         ContentStyle::new().white(),
@@ -62,14 +316,133 @@ pub static DEFAULT_THEME: Lazy<Theme> = Lazy::new(|| {
         "█".to_string(),
         "█".to_string(),
         "█".to_string(),
+    ).expect("default glyphs are one column wide").with_wall_style(
+        ContentStyle::new().dark_grey(),
+    ).with_age_colors(
+        // green through yellow to red
+        (0..AGE_COLORS)
+            .map(|step| {
+                let t = 1.0 / 3.0 * (1.0 - step as f32 / (AGE_COLORS - 1) as f32);
+                let (r, g, b) = hsv_to_rgb(t, 1.0, 1.0);
+                Color::Rgb { r, g, b }
+            })
+            .collect(),
+    ).with_history_colors(
+        vec![Color::DarkBlue, Color::Magenta, Color::DarkMagenta, Color::Cyan, Color::DarkRed],
     )
 });
 
+/// Themes `t` cycles through, starting with the default one.
+pub static THEMES: Lazy<Vec<(&'static str, Theme)>> = Lazy::new(|| {
+    let solid = |alive: ContentStyle, dead: ContentStyle, dead_glyph: &str| Theme::new(
+        dead,
+        alive,
+        dead,
+        alive,
+        dead_glyph.to_string(),
+        "█".to_string(),
+        dead_glyph.to_string(),
+        "█".to_string(),
+    ).expect("solid glyphs are one column wide");
+    vec![
+        ("lifecycle", DEFAULT_THEME.clone()),
+        ("classic", solid(ContentStyle::new().white(), ContentStyle::new().black(), "█")
+            .with_wall_style(ContentStyle::new().dark_grey())),
+        ("monochrome", solid(ContentStyle::new(), ContentStyle::new(), " ")
+            .with_wall_style(ContentStyle::new().dark_grey())),
+        ("matrix", Theme::new(
+            ContentStyle::new().black(),
+            ContentStyle::new().green(),
+            ContentStyle::new().dark_green(),
+            ContentStyle::new().white(),
+            "█".to_string(),
+            "█".to_string(),
+            "█".to_string(),
+            "█".to_string(),
+        ).expect("matrix glyphs are one column wide").with_wall_style(ContentStyle::new().dark_green())),
+        ("heatmap", solid(ContentStyle::new().red(), ContentStyle::new().black(), "█")
+            .with_wall_style(ContentStyle::new().dark_grey())
+            .with_age_colors(
+                // hot red for newborn cells cooling down to blue
+                (0..AGE_COLORS)
+                    .map(|step| {
+                        let (r, g, b) = hsv_to_rgb(2.0 / 3.0 * step as f32 / (AGE_COLORS - 1) as f32, 1.0, 1.0);
+                        Color::Rgb { r, g, b }
+                    })
+                    .collect(),
+            )
+            .with_heat(true)),
+    ]
+});
+
+
+/// Rectangle of terminal cells a board is drawn into.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Area {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+    pub topology: Topology,
+}
+
+impl Area {
+    pub fn of(board: &Board) -> Self {
+        Area {
+            x: 0,
+            y: 0,
+            width: screen_len(board.width()),
+            height: screen_len(board.height()),
+            topology: board.topology(),
+        }
+    }
+
+    /// Terminal position of the board cell `(x, y)`, only visible cells have one.
+    pub fn position(&self, (x, y): (usize, usize), cell_columns: u16) -> (u16, u16) {
+        let column = self.x as usize + layout_column(self.topology, (x, y)) * cell_columns as usize;
+        let row = self.y as usize + y;
+        debug_assert!(
+            column <= u16::MAX as usize && row <= u16::MAX as usize,
+            "cell ({}, {}) is outside of the terminal",
+            x,
+            y,
+        );
+        (column as u16, row as u16)
+    }
+}
+
+// hexagons take two cells' worth of columns, with every odd row shifted right by half of that
+fn layout_column(topology: Topology, (x, y): (usize, usize)) -> usize {
+    match topology {
+        Topology::Square => x,
+        Topology::Hex => 2 * x + y % 2,
+    }
+}
+
+// cell widths taken by a whole row of `board`, including the shift of odd hexagon rows
+fn layout_width(board: &Board) -> usize {
+    match board.topology() {
+        Topology::Square => board.width(),
+        Topology::Hex => 2 * board.width(),
+    }
+}
+
+/// Terminal cells taken by `len` board cells, boards may be larger than any terminal.
+pub fn screen_len(len: usize) -> u16 {
+    u16::try_from(len).unwrap_or(u16::MAX)
+}
 
+#[allow(clippy::too_many_arguments)]
 pub fn draw_board(
     theme: &Theme,
     stdout: &mut Stdout,
     board: &Board,
+    ages: &[u32],
+    age_colors: bool,
+    trails: Option<&[bool]>,
+    mask: Option<&Mask>,
+    history: Option<&HistoryLayer>,
+    area: Area,
 ) -> Result<()> {
     let dead_style = StyledContent::new(
         theme.dead_cell_style,
@@ -87,26 +460,194 @@ pub fn draw_board(
         theme.born_cell_style,
         theme.born_cell_content.as_str(),
     );
-    for entry in board.iter() {
-        let style = match entry.cell() {
-            Cell::Dead => dead_style,
-            Cell::Alive => alive_style,
-            Cell::Died => died_style,
-            Cell::Born => born_style,
+    let blank = " ".repeat(theme.cell_columns as usize);
+    let blank_style = StyledContent::new(ContentStyle::new(), blank.as_str());
+    let wall_style = StyledContent::new(
+        theme.wall_cell_style,
+        theme.dead_cell_content.as_str(),
+    );
+    let trail_style = StyledContent::new(
+        theme.trail_cell_style,
+        theme.alive_cell_content.as_str(),
+    );
+    // cells die at the walls of a bounded board, never change outside of the mask and
+    // don't exist outside of the board's shape, so make all of them visible
+    let on_wall = |x: usize, y: usize| {
+        let bounded = board.edges() == EdgeBehavior::Bounded
+            && (x == 0 || y == 0 || x + 1 == board.width() || y + 1 == board.height());
+        bounded || mask.is_some_and(|mask| !mask.contains((x, y))) || !board.in_shape((x, y))
+    };
+    let visible = |x: usize, y: usize| x < area.width as usize && y < area.height as usize;
+    for entry in board.iter().filter(|entry| visible(entry.x(), entry.y())) {
+        let age = ages[entry.y() * board.width() + entry.x()];
+        let fade = theme.fade_style(age)
+            .filter(|_| !entry.cell().is_alive())
+            .map(|style| StyledContent::new(style, theme.died_cell_content.as_str()));
+        let marked = history.and_then(|history| theme.history_style(history.get(entry.index())));
+        let style = match (entry.cell(), fade) {
+            (Cell::Alive | Cell::Born, _) if age_colors => {
+                StyledContent::new(theme.age_style(age), theme.alive_cell_content.as_str())
+            }
+            (_, Some(fade)) => fade,
+            (Cell::Dead, _) if on_wall(entry.x(), entry.y()) => wall_style,
+            (Cell::Dead, _) if trails.is_some_and(|trails| trails[entry.y() * board.width() + entry.x()]) => trail_style,
+            (Cell::Dead, _) if theme.blank_dead => blank_style,
+            (Cell::Dead, _) => dead_style,
+            (Cell::Alive, _) => alive_style,
+            (Cell::Died, _) => died_style,
+            (Cell::Born, _) => born_style,
         };
+        let style = marked.unwrap_or(style);
+        let (column, row) = area.position(entry.index(), theme.cell_columns);
         stdout
-            .queue(cursor::MoveTo(entry.x() as u16, entry.y() as u16))?
+            .queue(cursor::MoveTo(column, row))?
             .queue(style::PrintStyledContent(style))?;
     }
     stdout.flush()?;
     Ok(())
 }
 
+/// Dim arrows in the last visible column and row pointing at live cells of `board`
+/// that lie beyond `area`, so they can be found again.
+pub fn draw_offscreen_hints(theme: &Theme, stdout: &mut Stdout, board: &Board, area: Area) -> Result<()> {
+    let (width, height) = (area.width as usize, area.height as usize);
+    let Some((_, (right, bottom))) = board.live_bounds() else {
+        return Ok(());
+    };
+    if (right < width && bottom < height) || width == 0 || height == 0 {
+        return Ok(());
+    }
+    let pad = " ".repeat(theme.cell_columns as usize - 1);
+    let mut hint = |x: usize, y: usize, arrow: char| -> Result<()> {
+        let content = format!("{}{}", arrow, pad);
+        let (column, row) = area.position((x, y), theme.cell_columns);
+        stdout
+            .queue(cursor::MoveTo(column, row))?
+            .queue(style::PrintStyledContent(StyledContent::new(theme.wall_cell_style, content)))?;
+        Ok(())
+    };
+    for (y, row) in board.rows().take(height).enumerate() {
+        if row[width.min(row.len())..].iter().any(Cell::is_alive) {
+            hint(width - 1, y, '›')?;
+        }
+    }
+    for (x, col) in board.cols().take(width).enumerate() {
+        if col.skip(height).any(|cell| cell.is_alive()) {
+            hint(x, height - 1, '⌄')?;
+        }
+    }
+    if board.rows().skip(height).any(|row| row[width.min(row.len())..].iter().any(Cell::is_alive)) {
+        hint(width - 1, height - 1, '↘')?;
+    }
+    Ok(())
+}
+
+/// Whether any cell is alive in each block of `board` cut into at most `columns` blocks across
+/// and `rows` down, row by row.
+pub fn minimap(board: &Board, (columns, rows): (usize, usize)) -> Vec<Vec<bool>> {
+    let block = (board.width().div_ceil(columns.max(1)), board.height().div_ceil(rows.max(1)));
+    let (columns, rows) = (board.width().div_ceil(block.0), board.height().div_ceil(block.1));
+    if board.live_bounds().is_none() {
+        return vec![vec![false; columns]; rows];
+    }
+    (0..rows)
+        .map(|y| (0..columns).map(|x| board.population_in((x * block.0, y * block.1), block) > 0).collect())
+        .collect()
+}
+
+/// Minimap of a board larger than `area` in its top right corner, the blocks the area shows
+/// in a lighter shade.
+pub fn draw_minimap(theme: &Theme, stdout: &mut Stdout, board: &Board, area: Area) -> Result<()> {
+    let (width, height) = (area.width as usize, area.height as usize);
+    if (board.width() <= width && board.height() <= height) || width == 0 || height == 0 {
+        return Ok(());
+    }
+    let (right, _) = area.position((width - 1, 0), theme.cell_columns);
+    let right = right as usize + theme.cell_columns as usize;
+    let pixels = minimap(board, (min(MINIMAP_SIZE.0, right / 2), min(MINIMAP_SIZE.1, height / 2)));
+    let block = (board.width().div_ceil(pixels[0].len()), board.height().div_ceil(pixels.len()));
+    let left = right.saturating_sub(pixels[0].len());
+    for (y, row) in pixels.iter().enumerate() {
+        stdout.queue(cursor::MoveTo(screen_len(left), area.y + screen_len(y)))?;
+        for (x, &alive) in row.iter().enumerate() {
+            let shown = x * block.0 < width && y * block.1 < height;
+            let style = ContentStyle::new().white().on(if shown { Color::Grey } else { Color::DarkGrey });
+            stdout.queue(style::PrintStyledContent(StyledContent::new(style, if alive { '█' } else { ' ' })))?;
+        }
+    }
+    Ok(())
+}
+
+pub fn draw_separator(
+    stdout: &mut Stdout,
+    column: u16,
+    height: u16,
+) -> Result<()> {
+    for row in 0..height {
+        stdout
+            .queue(cursor::MoveTo(column, row))?
+            .queue(style::Print('│'))?;
+    }
+    Ok(())
+}
+
+/// Catalog in the top left corner of `area`, the selected name highlighted and previewed
+/// next to the list, everything clipped to `area`.
+pub fn draw_picker(
+    stdout: &mut Stdout,
+    catalog: &[(&str, Pattern)],
+    selected: usize,
+    area: Area,
+) -> Result<()> {
+    let name_width = catalog.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+    let preview = &catalog[selected].1;
+    // a blank row above and below, a blank column around the names and after the preview
+    let rows = max(catalog.len(), preview.height()) + 2;
+    let width = area.width as usize;
+    let background = ContentStyle::new().white().on_dark_grey();
+    let highlight = ContentStyle::new().black().on_white();
+    for row in 0..min(rows, area.height as usize) {
+        let entry = row.checked_sub(1).and_then(|index| catalog.get(index));
+        let name = format!(" {:<width$} ", entry.map_or("", |(name, _)| name), width = name_width);
+        let cells: String = (0..preview.width())
+            .map(|x| if row > 0 && preview.cells().contains(&(x, row - 1)) { '█' } else { ' ' })
+            .chain([' '])
+            .collect();
+        let style = if row == selected + 1 { highlight } else { background };
+        let name: String = name.chars().take(width).collect();
+        let cells: String = cells.chars().take(width.saturating_sub(name.chars().count())).collect();
+        stdout
+            .queue(cursor::MoveTo(area.x, area.y + screen_len(row)))?
+            .queue(style::PrintStyledContent(StyledContent::new(style, name)))?
+            .queue(style::PrintStyledContent(StyledContent::new(background, cells)))?;
+    }
+    Ok(())
+}
+
+/// Where keys typed into the terminal go.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum InputMode {
+    #[default]
+    Board,
+    // the `:` command line
+    Command,
+    // the pattern catalog overlay
+    Picker,
+}
+
 #[derive(Debug)]
+#[cfg_attr(not(feature = "net"), allow(dead_code))]
 pub enum BoardEvent {
     MouseClick {
         x: u16,
         y: u16,
+        drag: bool,
+        // draws a line from the last click instead of toggling one cell
+        shift: bool,
+    },
+    Hover {
+        x: u16,
+        y: u16,
     },
     Exit,
     Resized {
@@ -115,139 +656,2079 @@ pub enum BoardEvent {
     },
     Pause,
     Speed(bool),
+    StepBack,
+    HueCycle,
+    Palette,
+    NextTheme,
+    Classify,
+    AgeColors,
+    BlankDead,
+    Trace,
+    ClearTrails,
+    Minimap,
+    FrameTimes,
+    Edges,
+    Symmetry,
+    Step,
+    Clear,
+    Invert,
+    MaskMode,
+    Set {
+        x: usize,
+        y: usize,
+    },
+    Rules(Rules),
+    Get(Sender<String>),
+    NewTab,
+    NextTab,
+    SelectTab(usize),
+    StepAll,
+    NextRules,
+    Split,
+    DrawMode(DrawMode),
+    SaveSession,
+    SaveCoords,
+    Command,
+    Input(char),
+    Paste(String),
+    Backspace,
+    Submit,
+    Cancel,
+    Picker,
+    PickerMove(isize),
 }
 
-pub fn handle_events(
-    poll_duration: Duration,
-) -> Option<BoardEvent> {
-    if poll(poll_duration).ok()? {
-        return match read().ok()? {
-            Event::Mouse(
-                MouseEvent {
-                    kind: MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left),
-                    column: x,
-                    row: y,
-                    ..
-                }) => {
-                Some(BoardEvent::MouseClick { x, y })
-            }
-            Event::Key(KeyEvent { code: KeyCode::Char('q'), .. }) => {
-                Some(BoardEvent::Exit)
-            }
-            Event::Key(KeyEvent { code: KeyCode::Char(' '), .. }) => {
-                Some(BoardEvent::Pause)
-            }
-            Event::Key(KeyEvent { code: KeyCode::Char('+'), .. }) => {
-                Some(BoardEvent::Speed(true))
+/// Reads terminal input on its own thread, the only producer of input events,
+/// until the receiving end is gone.
+pub fn spawn_input(sender: Sender<BoardEvent>) {
+    thread::spawn(move || {
+        let mut mode = InputMode::Board;
+        while let Ok(event) = read() {
+            if let Some(event) = handle_event(event, &mut mode) {
+                if sender.send(event).is_err() {
+                    break;
+                }
             }
-            Event::Key(KeyEvent { code: KeyCode::Char('-'), .. }) => {
-                Some(BoardEvent::Speed(false))
+        }
+    });
+}
+
+/// Translates terminal input, `mode` tracks whether keys go to the board, the `:` command line
+/// or the pattern picker.
+pub fn handle_event(event: Event, mode: &mut InputMode) -> Option<BoardEvent> {
+    // raw mode turns Ctrl+C into a key press, which has to quit the same way `q` does so the
+    // terminal is restored, whatever the keys currently go to
+    if let Event::Key(KeyEvent { code: KeyCode::Char('c'), modifiers, .. }) = event {
+        if modifiers.contains(KeyModifiers::CONTROL) {
+            return Some(BoardEvent::Exit);
+        }
+    }
+    if let (InputMode::Command | InputMode::Picker, Event::Key(KeyEvent { code, .. })) = (*mode, &event) {
+        return match (*mode, code) {
+            (InputMode::Command, KeyCode::Char(c)) => Some(BoardEvent::Input(*c)),
+            (InputMode::Command, KeyCode::Backspace) => Some(BoardEvent::Backspace),
+            (InputMode::Picker, KeyCode::Up) => Some(BoardEvent::PickerMove(-1)),
+            (InputMode::Picker, KeyCode::Down) => Some(BoardEvent::PickerMove(1)),
+            (_, KeyCode::Enter) => {
+                *mode = InputMode::Board;
+                Some(BoardEvent::Submit)
             }
-            Event::Resize(x, y) => {
-                Some(BoardEvent::Resized { x, y })
+            (_, KeyCode::Esc) | (InputMode::Picker, KeyCode::Char('p')) => {
+                *mode = InputMode::Board;
+                Some(BoardEvent::Cancel)
             }
-            _ => None
+            _ => None,
         };
-    } else {
-        None
+    }
+    match event {
+        Event::Mouse(
+            MouseEvent {
+                kind: kind @ (MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left)),
+                column: x,
+                row: y,
+                modifiers,
+            }) => {
+            let drag = matches!(kind, MouseEventKind::Drag(_));
+            let shift = !drag && modifiers.contains(KeyModifiers::SHIFT);
+            Some(BoardEvent::MouseClick { x, y, drag, shift })
+        }
+        Event::Mouse(MouseEvent { kind: MouseEventKind::Moved, column: x, row: y, .. }) => {
+            Some(BoardEvent::Hover { x, y })
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('q'), .. }) => {
+            Some(BoardEvent::Exit)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char(' '), .. }) => {
+            Some(BoardEvent::Pause)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('+'), .. }) => {
+            Some(BoardEvent::Speed(true))
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('-'), .. }) => {
+            Some(BoardEvent::Speed(false))
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('s'), .. }) => {
+            Some(BoardEvent::Step)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('b'), .. }) => {
+            Some(BoardEvent::StepBack)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('h'), .. }) => {
+            Some(BoardEvent::HueCycle)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('t'), .. }) => {
+            Some(BoardEvent::NextTheme)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('l'), .. }) => {
+            Some(BoardEvent::Palette)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('g'), .. }) => {
+            Some(BoardEvent::AgeColors)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('d'), .. }) => {
+            Some(BoardEvent::BlankDead)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('T'), .. }) => {
+            Some(BoardEvent::Trace)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('C'), .. }) => {
+            Some(BoardEvent::ClearTrails)
+        }
+        // capital, since `m` already cycles the drawing symmetry
+        Event::Key(KeyEvent { code: KeyCode::Char('M'), .. }) => {
+            Some(BoardEvent::Minimap)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('F'), .. }) => {
+            Some(BoardEvent::FrameTimes)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('k'), .. }) => {
+            Some(BoardEvent::MaskMode)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('i'), .. }) => {
+            Some(BoardEvent::Invert)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('e'), .. }) => {
+            Some(BoardEvent::Edges)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('m'), .. }) => {
+            Some(BoardEvent::Symmetry)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('n'), .. }) => {
+            Some(BoardEvent::NewTab)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Tab, .. }) => {
+            Some(BoardEvent::NextTab)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char(digit @ '1'..='9'), .. }) => {
+            Some(BoardEvent::SelectTab(digit as usize - '1' as usize))
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('a'), .. }) => {
+            Some(BoardEvent::StepAll)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('r'), .. }) => {
+            Some(BoardEvent::NextRules)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('v'), .. }) => {
+            Some(BoardEvent::Split)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('c'), .. }) => {
+            Some(BoardEvent::Classify)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('S'), .. }) => {
+            Some(BoardEvent::SaveSession)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('W'), .. }) => {
+            Some(BoardEvent::SaveCoords)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('L'), .. }) => {
+            Some(BoardEvent::DrawMode(DrawMode::Line))
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('R'), .. }) => {
+            Some(BoardEvent::DrawMode(DrawMode::Rect))
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char(':'), .. }) => {
+            *mode = InputMode::Command;
+            Some(BoardEvent::Command)
+        }
+        Event::Key(KeyEvent { code: KeyCode::Char('p'), .. }) => {
+            *mode = InputMode::Picker;
+            Some(BoardEvent::Picker)
+        }
+        Event::Paste(text) if *mode != InputMode::Picker => {
+            *mode = InputMode::Command;
+            Some(BoardEvent::Paste(text))
+        }
+        Event::Resize(x, y) => {
+            Some(BoardEvent::Resized { x, y })
+        }
+        _ => None
     }
 }
 
-pub fn get_size() -> Result<(u16, u16)> {
-    terminal::enable_raw_mode().unwrap();
-    terminal::size()
+pub fn draw_status(
+    stdout: &mut Stdout,
+    row: u16,
+    width: u16,
+    status: &str,
+) -> Result<()> {
+    // writing past the last column of the last row would scroll the whole screen
+    let mut columns = 0;
+    let status: String = status.chars()
+        .take_while(|c| {
+            columns += c.width().unwrap_or(0);
+            columns < width as usize
+        })
+        .collect();
+    stdout
+        .queue(cursor::MoveTo(0, row))?
+        .queue(Clear(ClearType::CurrentLine))?
+        .queue(style::Print(status))?;
+    stdout.flush()?;
+    Ok(())
 }
 
-pub fn main_loop() -> Result<()> {
-    terminal::enable_raw_mode()?;
-    let mut board = {
-        let (w, h) = get_size()?;
-        Board::new(w as usize, h as usize)
-    };
+pub fn get_size() -> (u16, u16) {
+    usable_size(terminal::size().ok())
+}
+
+/// Cells of the board `main_loop` starts with, unless a pattern makes it larger.
+pub fn initial_board_size(topology: Topology) -> (usize, usize) {
+    let (columns, rows) = get_size();
+    let cell_columns = THEMES[0].1.cell_columns();
+    (board_width(columns, cell_columns, topology) as usize, board_height(rows) as usize)
+}
+
+// headless CI runners and detached sessions may report a zero sized terminal
+fn usable_size(reported: Option<(u16, u16)>) -> (u16, u16) {
+    match reported {
+        Some((columns, rows)) if columns > 0 && rows > 0 => (columns, rows),
+        _ => FALLBACK_SIZE,
+    }
+}
 
-    for i in 0..min(25usize, min(board.width(), board.height())) {
-        board.index_mut((i, i)).flip();
+// used without a usable terminal, e.g. when a `--max-gen` run is piped into a file
+const FALLBACK_SIZE: (u16, u16) = (80, 24);
+// shortest time between two redraws for the hovered cell
+const HOVER_INTERVAL: Duration = Duration::from_millis(50);
+// terminal cells the minimap takes at most, and never more than half of the board's width and height
+const MINIMAP_SIZE: (usize, usize) = (24, 8);
+// space pressed again sooner than this is taken for key repeat and does not toggle pausing
+const PAUSE_DEBOUNCE: Duration = Duration::from_millis(200);
+// generations per second, `+` and `-` multiply or divide it by `SPEED_STEP` within the limits
+const DEFAULT_SPEED: f64 = 1000.0 / 64.0;
+const SPEED_STEP: f64 = 1.25;
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 1000.0;
+const SOUP_DENSITY: f64 = 0.35;
+// how far `c` looks ahead for the board to repeat, and the longest period it recognizes
+const CLASSIFY_GENERATIONS: u64 = 2000;
+const CLASSIFY_PERIOD: usize = 64;
+const DEMO_MARGIN: usize = 1;
+const KEYFRAME_INTERVAL: u64 = 32;
+const KEYFRAME_CAPACITY: usize = 64;
+const HUE_PERIOD: u64 = 360;
+const AGE_COLORS: usize = 8;
+// generations a live cell spends on each age color
+const AGE_STEP: u32 = 4;
+// where `S` saves the session without `--session`
+#[cfg(feature = "serde")]
+const DEFAULT_SESSION: &str = "session.json";
+// where `W` saves the live cells without `--coords`
+const DEFAULT_COORDS: &str = "board.coords";
+// generations between flushes of `--record-text`, so a killed run still leaves whole boards
+const RECORD_FLUSH_INTERVAL: u64 = 16;
+
+// terminals can report zero columns while being resized, but boards are never empty
+fn board_width(terminal_width: u16, cell_columns: u16, topology: Topology) -> u16 {
+    max(columns_fitting(terminal_width, cell_columns, topology), 1)
+}
+
+// board columns fully shown within `terminal_width`
+fn columns_fitting(terminal_width: u16, cell_columns: u16, topology: Topology) -> u16 {
+    let cells = terminal_width / cell_columns;
+    match topology {
+        Topology::Square => cells,
+        Topology::Hex => cells / 2,
     }
+}
 
+fn ramp_speed(speed: f64, faster: bool) -> f64 {
+    let speed = if faster { speed * SPEED_STEP } else { speed / SPEED_STEP };
+    speed.clamp(MIN_SPEED, MAX_SPEED)
+}
+
+// the last terminal row is taken by the status bar
+fn board_height(terminal_height: u16) -> u16 {
+    max(terminal_height, 2) - 1
+}
+
+// split view draws two boards with a one column separator between them
+fn split_width(terminal_width: u16, cell_columns: u16, topology: Topology) -> u16 {
+    board_width((max(terminal_width, 3) - 1) / 2, cell_columns, topology)
+}
+
+// which of the shown universes a screen position falls on, and the cell there
+fn board_at(shown: &[Universe], (x, y): (u16, u16), cell_columns: u16) -> (usize, (usize, usize)) {
+    let mut x = x as usize;
+    let split_at = layout_width(&shown[0].board) * cell_columns as usize;
+    let target = if shown.len() > 1 && x > split_at {
+        x -= split_at + 1;
+        1
+    } else {
+        0
+    };
+    let (column, y) = (x / cell_columns as usize, y as usize);
+    let x = match shown[target].board.topology() {
+        Topology::Square => column,
+        Topology::Hex => column.saturating_sub(y % 2) / 2,
+    };
+    (target, (x, y))
+}
+
+fn next_preset(rules: &Rules) -> Rules {
+    let presets = [Rules::conway(), Rules::highlife(), Rules::day_and_night()];
+    let next = presets.iter()
+        .position(|preset| preset == rules)
+        .map_or(0, |index| (index + 1) % presets.len());
+    presets[next]
+}
+
+/// Undoes what `main_loop` sets up in an interactive terminal, on a normal exit as well as
+/// after an error or a panic. Every step is attempted, the first failure is returned.
+pub fn cleanup_terminal() -> Result<()> {
+    if !stdin().is_terminal() {
+        return Ok(());
+    }
     let mut stdout = stdout();
-    stdout.execute(EnableMouseCapture)?;
+    let steps = [
+        stdout.execute(DisableBracketedPaste).map(drop),
+        stdout.execute(DisableMouseCapture).map(drop),
+        stdout.execute(Clear(ClearType::All)).map(drop),
+        stdout.execute(cursor::MoveTo(0, 0)).map(drop),
+        terminal::disable_raw_mode(),
+    ];
+    steps.into_iter().collect()
+}
 
-    let mut frame_duration = Duration::from_millis(64);
+/// The board a run starts from as the flags describe it, with the LifeHistory states of its pattern.
+pub fn initial_board(args: &Args) -> (Board, Option<HistoryLayer>) {
+    let mut life_history = None;
+    let mut board = {
+        let (w, h) = initial_board_size(args.grid);
+        match args.seed {
+            _ if args.demo => {
+                // large enough for the gun even on small terminals, which only show part of it
+                let gun = Pattern::gosper_glider_gun();
+                let (w, h) = (max(w, gun.width() + 2 * DEMO_MARGIN), max(h, gun.height() + 2 * DEMO_MARGIN));
+                let mut board = Board::new(w, h);
+                board.stamp(&gun, (DEMO_MARGIN, DEMO_MARGIN));
+                board
+            }
+            Some(seed) => Board::random_symmetric(w, h, SOUP_DENSITY, seed, args.soup),
+            None => match &args.pattern {
+                Some(pattern) => {
+                    let (w, h) = (max(w, pattern.width()), max(h, pattern.height()));
+                    let mut board = Board::new(w, h);
+                    let (cx, cy) = args.at.unwrap_or_else(|| board.center_of());
+                    let corner = (cx.saturating_sub(pattern.width() / 2), cy.saturating_sub(pattern.height() / 2));
+                    board.stamp(pattern, corner);
+                    if let Some(states) = &args.life_history {
+                        let mut layer = HistoryLayer::new(w, h);
+                        layer.stamp(states, corner);
+                        life_history = Some(layer);
+                    }
+                    board
+                }
+                None => Board::new(w, h),
+            },
+        }
+    };
+    // gliders wrapping around would crash back into the gun
+    board.set_edges(if args.demo { EdgeBehavior::Bounded } else { args.edges });
+    board.set_topology(args.grid);
+    if args.shape == Shape::Circle {
+        board.set_shape(Some(Mask::circle(board.width(), board.height())));
+    }
 
-    fn remaining_time(start: Instant, frame_duration: Duration) -> Option<Duration> {
-        let now = Instant::now();
-        let passed = now - start;
-        if passed >= frame_duration {
-            None
-        } else {
-            Some(frame_duration - passed)
+    for &cell in &args.cells {
+        board.set(cell, Cell::Alive);
+    }
+    if args.rule1d.is_some() && args.seed.is_none() && !args.demo && args.pattern.is_none() && args.cells.is_empty() {
+        // the classic start of a one-dimensional automaton, a single cell in the first row
+        let (x, _) = args.at.unwrap_or_else(|| board.center_of());
+        board.set((x % board.width(), 0), Cell::Alive);
+    } else if args.seed.is_none() && !args.demo && args.pattern.is_none() && args.cells.is_empty() {
+        let (width, height) = (board.width(), board.height());
+        let (cx, cy) = args.at.unwrap_or_else(|| board.center_of());
+        let length = min(25usize, min(width, height));
+        for i in 0..length {
+            let x = (cx % width + width - length / 2 + i) % width;
+            let y = (cy % height + height - length / 2 + i) % height;
+            board.index_mut((x, y)).flip();
         }
     }
+    (board, life_history)
+}
 
-    let mut pause_state = PauseState::Disabled;
-    let mut last_updated = Instant::now();
+pub fn main_loop(args: Args) -> Result<RunStats> {
+    // input is only read from a terminal, so runs bounded by `--max-gen` also work without one
+    let interactive = stdin().is_terminal();
+    if interactive {
+        terminal::enable_raw_mode()?;
+    }
+    let color_support = ColorSupport::detect();
+    let themes: Vec<_> = THEMES.iter()
+        .map(|(name, theme)| (*name, theme.clone().with_fade(args.fade).for_support(color_support)))
+        .collect();
+    let cell_columns = themes[0].1.cell_columns();
+    let (columns, rows) = get_size();
+    let (board, life_history) = initial_board(&args);
 
-    'outer: loop {
-        let start = Instant::now();
-        let should_compute_state = Instant::now() > last_updated + frame_duration;
-        draw_board(&DEFAULT_THEME, &mut stdout, &board)?;
-
-        while let Some(timeout) = remaining_time(start, Duration::from_millis(16)) {
-            if let Some(event) = handle_events(timeout) {
-                match event {
-                    BoardEvent::MouseClick { x, y } => {
-                        let x = x as usize;
-                        let y = y as usize;
-                        if board.check_index((x, y)) {
-                            board.index_mut((x, y)).flip();
-                        }
-                    }
-                    BoardEvent::Exit => {
-                        break 'outer;
-                    }
-                    BoardEvent::Resized { x, y } => {
-                        resize(&mut board, x as usize, y as usize);
-                    }
-                    BoardEvent::Pause => {
-                        if pause_state != PauseState::Disabled {
-                            pause_state = PauseState::Disabled;
-                        } else {
-                            pause_state = PauseState::JustEnabled;
-                        }
-                    }
-                    BoardEvent::Speed(increase) => {
-                        if increase {
-                            frame_duration /= 2;
-                        } else {
-                            frame_duration *= 2;
-                        }
+    // the input and control threads produce events, the loop below only drains them,
+    // `sender` stays alive so the channel is never disconnected
+    let (sender, events): (Sender<BoardEvent>, Receiver<BoardEvent>) = mpsc::channel();
+    if interactive {
+        spawn_input(sender.clone());
+    }
+    #[cfg(feature = "net")]
+    if let Some(address) = &args.control {
+        crate::control::listen(address, sender.clone())?;
+    }
+
+    let mut stdout = stdout();
+    let mut renderer: Box<dyn Renderer> = Box::new(TerminalRenderer::new());
+    if interactive {
+        stdout.execute(EnableMouseCapture)?;
+        stdout.execute(EnableBracketedPaste)?;
+    }
+
+    let mut universe = Universe::new(board);
+    universe.life_history = life_history;
+    universe.meta = args.pattern_meta.clone();
+    if let Some(rules) = universe.meta.rules {
+        universe.rules = rules;
+    }
+    universe.rule1d = args.rule1d;
+    let mut state = AppState::new(args, universe, themes, (columns, rows), cell_columns);
+
+    #[cfg(feature = "serde")]
+    if let Some(path) = state.args.session.as_ref().filter(|path| path.exists()) {
+        let session = Session::load(path)?;
+        let mut universe = Universe::new(session.board);
+        universe.rules = session.rules;
+        universe.generation = session.generation;
+        // the keyframe of the new universe is not generation 0
+        universe.history.clear();
+        universe.edited();
+        state.universes = vec![universe];
+        state.speed = session.speed.clamp(MIN_SPEED, MAX_SPEED);
+        if session.paused && !state.args.manual {
+            state.paused = true;
+        }
+        for option in session.theme.split('+') {
+            match option {
+                "hue" => state.hue_cycle = true,
+                "plain" => state.plain_palette = true,
+                "ages" => state.age_colors = true,
+                "blank" => state.blank_dead = true,
+                "trace" => state.trace = true,
+                name => {
+                    if let Some(index) = state.themes.iter().position(|(theme, _)| *theme == name) {
+                        state.theme_index = index;
                     }
                 }
             }
         }
+    }
 
+    if let Some(path) = state.args.coords.as_ref().filter(|path| path.exists()) {
+        let mut board = Board::load_coords(&std::fs::read_to_string(path)?)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("`{}`: {}", path.display(), err)))?;
+        board.set_edges(state.args.edges);
+        board.set_topology(state.args.grid);
+        let mut universe = Universe::new(board);
+        universe.rules = state.universes[0].rules;
+        universe.rule1d = state.args.rule1d;
+        state.universes = vec![universe];
+    }
 
-        let is_paused = match pause_state {
-            PauseState::Disabled => false,
-            PauseState::JustEnabled => {
-                pause_state = PauseState::Activated;
-                false
-            }
-            _ => true,
-        };
-        if should_compute_state && !is_paused {
-            next_state(&mut board);
-            last_updated = Instant::now();
+    state.record = match &state.args.record_text {
+        Some(path) => {
+            let mut out = BufWriter::new(File::create(path)?);
+            record_text(&mut out, &state.universes[0].board, state.universes[0].generation)?;
+            Some(out)
         }
-    }
-    stdout.execute(DisableMouseCapture)?;
-    stdout.execute(Clear(ClearType::All))?;
-    terminal::disable_raw_mode()
-}
+        None => None,
+    };
+    state.csv = match &state.args.log_csv {
+        Some(path) => {
+            let mut out = BufWriter::new(File::create(path)?);
+            writeln!(out, "generation,population,births,deaths")?;
+            log_csv(&mut out, &state.universes[0].board, state.universes[0].generation)?;
+            Some(out)
+        }
+        None => None,
+    };
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum PauseState {
-    Disabled,
-    JustEnabled,
-    Activated,
+    'outer: loop {
+        let start = Instant::now();
+        let shown: &[Universe] = match &state.split {
+            Some(pair) => pair,
+            None => std::slice::from_ref(&state.universes[state.active]),
+        };
+        let universe = &shown[0];
+        let base_theme = &state.themes[state.theme_index].1;
+        let shifted;
+        let theme = if state.hue_cycle {
+            let t = (universe.generation % HUE_PERIOD) as f32 / HUE_PERIOD as f32;
+            shifted = base_theme.hue_shifted(t).for_support(color_support);
+            &shifted
+        } else {
+            base_theme
+        };
+        let plain;
+        let theme = if state.plain_palette {
+            plain = theme.plain();
+            &plain
+        } else {
+            theme
+        };
+        let blanked;
+        let theme = if state.blank_dead {
+            blanked = theme.clone().with_blank_dead(true);
+            &blanked
+        } else {
+            theme
+        };
+        let draw_start = Instant::now();
+        let mut x = 0;
+        for (index, shown) in shown.iter().enumerate().filter(|_| state.picker.is_none()) {
+            if index > 0 {
+                draw_separator(&mut stdout, x, min(screen_len(shown.board.height()), board_height(state.rows)))?;
+                x += 1;
+            }
+            let area = Area {
+                x,
+                width: min(screen_len(shown.board.width()), columns_fitting(state.columns.saturating_sub(x), state.cell_columns, shown.board.topology())),
+                height: min(screen_len(shown.board.height()), board_height(state.rows)),
+                ..Area::of(&shown.board)
+            };
+            let frame = Frame {
+                theme,
+                ages: &shown.ages,
+                age_colors: state.age_colors || theme.heat,
+                trails: Some(shown.visited.as_slice()).filter(|_| state.trace),
+                minimap: state.minimap,
+                mask: shown.mask.as_ref(),
+                history: shown.life_history.as_ref(),
+                area,
+            };
+            renderer.draw(&shown.board, &frame)?;
+            x = x.saturating_add(screen_len(layout_width(&shown.board)).saturating_mul(state.cell_columns));
+        }
+        if let Some(selected) = state.picker {
+            let area = Area {
+                x: 0,
+                y: 0,
+                width: state.columns,
+                height: board_height(state.rows),
+                topology: Topology::Square,
+            };
+            draw_picker(&mut stdout, &state.catalog, selected, area)?;
+        }
+        if let Some(frame_times) = &mut state.frame_times {
+            frame_times.draw.record(draw_start.elapsed());
+        }
+        let rules = shown.iter()
+            .map(|universe| match universe.rule1d {
+                Some(rule) => rule.to_string(),
+                None => universe.rules.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" vs ");
+        let mut status = format!("gen {} | {} | {}", universe.generation, rules, universe.board.edges());
+        if universe.board.topology() == Topology::Hex {
+            status.push_str(" | hex");
+        }
+        if state.split.is_none() && state.universes.len() > 1 {
+            status = format!("tab {}/{} | {}", state.active + 1, state.universes.len(), status);
+            if !state.step_all {
+                status.push_str(" | stepping active tab");
+            }
+        }
+        if state.args.manual {
+            status.push_str(" | manual");
+        } else if state.paused {
+            status = format!("{} | {:.1} gen/s | paused", status, state.speed);
+        } else {
+            status = format!("{} | {:.1} gen/s", status, state.speed);
+        }
+        if universe.rewound {
+            status.push_str(" | reconstructed");
+        }
+        if universe.rules.is_inverted(universe.generation) {
+            status.push_str(" | B0 inverted");
+        }
+        if state.trace {
+            status.push_str(" | trace");
+        }
+        if state.mask_mode {
+            status.push_str(" | drag to mask");
+        }
+        if state.symmetry != Symmetry::None {
+            status = format!("{} | mirror {}", status, state.symmetry);
+        }
+        if state.draw_mode != DrawMode::Point {
+            let click = if state.draw_anchor.is_some() { "click the end" } else { "click the start" };
+            status = format!("{} | {}: {}", status, state.draw_mode, click);
+        }
+        if let Some((board, (x, y))) = state.hover.and_then(|(target, index)| Some((&shown.get(target)?.board, index))) {
+            if board.check_index((x, y)) {
+                status = format!("{} | ({}, {}) {} neighbours", status, x, y, live_neighbours(board, (x, y)));
+            }
+        }
+        if let Some(frame_times) = &state.frame_times {
+            status = format!("{} | {}", status, frame_times);
+        }
+        if let Some(message) = &state.message {
+            status = format!("{} | {}", status, message);
+        }
+        if let Some(command) = &state.command {
+            // pasted line breaks and tabs would move the cursor out of the status line
+            status = format!(":{}", command.chars().map(|c| if c.is_control() { ' ' } else { c }).collect::<String>());
+        }
+        if state.picker.is_some() {
+            status = "↑/↓ choose, enter stamps at the cursor, esc closes".to_string();
+        }
+        draw_status(&mut stdout, min(screen_len(universe.board.height()), board_height(state.rows)), state.columns, &status)?;
+
+        // a paused board only changes on input, so it may wait for it longer than a frame
+        let idle = state.args.idle_frame.filter(|_| state.paused);
+        let deadline = start + idle.unwrap_or(state.args.render_interval);
+        while let Ok(event) = events.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            let shown_now = state.apply(event);
+            if state.exit {
+                break 'outer;
+            }
+            if state.clear {
+                stdout.queue(Clear(ClearType::All))?;
+                state.clear = false;
+            }
+            if idle.is_some() && shown_now {
+                break;
+            }
+        }
+        state.tick(Instant::now())?;
+        if state.finished() {
+            break;
+        }
+    }
+    if let Some(out) = &mut state.record {
+        out.flush()?;
+    }
+    cleanup_terminal()?;
+    Ok(state.stats())
+}
+
+/// What the main loop keeps track of between frames, changed by input events and the passing of
+/// time only, so it can be driven without a terminal.
+pub struct AppState {
+    args: Args,
+    universes: Vec<Universe>,
+    active: usize,
+    step_all: bool,
+    split: Option<[Universe; 2]>,
+    // generations per second
+    speed: f64,
+    paused: bool,
+    // the frame pausing is pressed in still takes the generations due, none are dropped
+    step_once: bool,
+    // when space was last pressed, whether it toggled pausing or not
+    last_pause_key: Option<Instant>,
+    last_updated: Instant,
+    // generations asked for with `Step` since the last tick
+    requested_steps: u32,
+    themes: Vec<(&'static str, Theme)>,
+    theme_index: usize,
+    hue_cycle: bool,
+    // plain palette only tells alive from dead, without born and died highlights
+    plain_palette: bool,
+    age_colors: bool,
+    blank_dead: bool,
+    // dead cells that have ever been alive are drawn as trails
+    trace: bool,
+    // the whole board in miniature in the top right corner, when it does not fit the terminal
+    minimap: bool,
+    symmetry: Symmetry,
+    draw_mode: DrawMode,
+    draw_anchor: Option<(usize, usize)>,
+    // board and cell of the last click toggling a cell, where a shift-click starts its line
+    last_click: Option<(usize, (usize, usize))>,
+    cursor_at: (usize, usize),
+    // the board under the mouse and the cell on it
+    hover: Option<(usize, (usize, usize))>,
+    hover_drawn: Instant,
+    // soups drawn by `--screensaver` so far, each one from the next seed
+    reseeds: u64,
+    frame_times: Option<FrameTimes>,
+    // while in mask mode mouse drags select the region that is simulated
+    mask_mode: bool,
+    mask_anchor: Option<(usize, usize)>,
+    // pattern being typed after `:`, and the outcome of the last one
+    command: Option<String>,
+    catalog: Vec<(&'static str, Pattern)>,
+    // selected catalog entry while the picker is open, the boards underneath are not redrawn
+    picker: Option<usize>,
+    message: Option<String>,
+    columns: u16,
+    rows: u16,
+    cell_columns: u16,
+    record: Option<BufWriter<File>>,
+    csv: Option<BufWriter<File>>,
+    // set by events after which the whole screen has to be redrawn
+    clear: bool,
+    exit: bool,
+}
+
+impl AppState {
+    pub fn new(args: Args, universe: Universe, themes: Vec<(&'static str, Theme)>, (columns, rows): (u16, u16), cell_columns: u16) -> Self {
+        AppState {
+            // manual mode never advances on its own, only on `Step`
+            paused: args.manual,
+            step_once: false,
+            last_pause_key: None,
+            args,
+            cursor_at: universe.board.center_of(),
+            universes: vec![universe],
+            active: 0,
+            step_all: true,
+            split: None,
+            speed: DEFAULT_SPEED,
+            last_updated: Instant::now(),
+            requested_steps: 0,
+            themes,
+            theme_index: 0,
+            hue_cycle: false,
+            plain_palette: false,
+            age_colors: false,
+            blank_dead: false,
+            trace: false,
+            minimap: false,
+            symmetry: Symmetry::None,
+            draw_mode: DrawMode::Point,
+            draw_anchor: None,
+            last_click: None,
+            hover: None,
+            hover_drawn: Instant::now(),
+            reseeds: 0,
+            frame_times: None,
+            mask_mode: false,
+            mask_anchor: None,
+            command: None,
+            catalog: Pattern::catalog(),
+            picker: None,
+            message: None,
+            columns,
+            rows,
+            cell_columns,
+            record: None,
+            csv: None,
+            clear: false,
+            exit: false,
+        }
+    }
+
+    /// Takes in one input event, returns whether it is worth a redraw before the frame is up.
+    pub fn apply(&mut self, event: BoardEvent) -> bool {
+        let targets: &mut [Universe] = match &mut self.split {
+            Some(pair) => pair,
+            None => std::slice::from_mut(&mut self.universes[self.active]),
+        };
+        match event {
+            BoardEvent::MouseClick { x, y, drag, shift } => {
+                let (target, (x, y)) = board_at(targets, (x, y), self.cell_columns);
+                let universe = &mut targets[target];
+                if universe.board.check_index((x, y)) && self.mask_mode {
+                    // dragging spans the mask from where the button went down
+                    let anchor = match self.mask_anchor {
+                        Some(anchor) if drag => anchor,
+                        _ => (x, y),
+                    };
+                    self.mask_anchor = Some(anchor);
+                    let (width, height) = (universe.board.width(), universe.board.height());
+                    universe.mask = Some(Mask::rect(width, height, anchor, (x, y)));
+                } else if universe.board.check_index((x, y)) && self.draw_mode != DrawMode::Point {
+                    // the first click anchors the shape, the second one draws it
+                    self.cursor_at = (x, y);
+                    match self.draw_anchor.take() {
+                        _ if drag => self.draw_anchor = Some((x, y)),
+                        None => self.draw_anchor = Some((x, y)),
+                        Some(anchor) => {
+                            let (width, height) = (universe.board.width(), universe.board.height());
+                            for index in self.draw_mode.cells(anchor, (x, y)) {
+                                for index in self.symmetry.mirrored(index, width, height) {
+                                    universe.board[index] = Cell::Alive;
+                                }
+                            }
+                            universe.edited();
+                        }
+                    }
+                } else if universe.board.check_index((x, y)) {
+                    self.cursor_at = (x, y);
+                    let (width, height) = (universe.board.width(), universe.board.height());
+                    match self.last_click.filter(|&(board, start)| shift && board == target && universe.board.check_index(start)) {
+                        Some((_, start)) => {
+                            for index in line_cells(start, (x, y)) {
+                                for index in self.symmetry.mirrored(index, width, height) {
+                                    universe.board[index] = Cell::Alive;
+                                }
+                            }
+                        }
+                        None => {
+                            for index in self.symmetry.mirrored((x, y), width, height) {
+                                universe.board.index_mut(index).flip();
+                            }
+                        }
+                    }
+                    self.last_click = Some((target, (x, y)));
+                    universe.edited();
+                }
+            }
+            BoardEvent::Hover { x, y } => {
+                self.hover = Some(board_at(targets, (x, y), self.cell_columns)).filter(|_| self.args.hover);
+                // the mouse moves far more often than the status line needs to follow it
+                if self.hover_drawn.elapsed() < HOVER_INTERVAL {
+                    return false;
+                }
+                self.hover_drawn = Instant::now();
+            }
+            BoardEvent::Exit => {
+                self.exit = true;
+            }
+            BoardEvent::Resized { x, y } => {
+                (self.columns, self.rows) = (x, y);
+                for universe in self.universes.iter_mut() {
+                    universe.resize(board_width(self.columns, self.cell_columns, universe.board.topology()) as usize, board_height(self.rows) as usize);
+                }
+                for universe in self.split.iter_mut().flatten() {
+                    universe.resize(split_width(self.columns, self.cell_columns, universe.board.topology()) as usize, board_height(self.rows) as usize);
+                }
+                self.clear = true;
+            }
+            BoardEvent::Pause => {
+                return self.toggle_pause(Instant::now());
+            }
+            BoardEvent::Speed(faster) => {
+                self.speed = ramp_speed(self.speed, faster);
+            }
+            BoardEvent::Step => {
+                self.requested_steps += 1;
+            }
+            BoardEvent::Clear => {
+                for universe in targets.iter_mut() {
+                    universe.board.fill(Cell::Dead);
+                    universe.edited();
+                }
+            }
+            BoardEvent::MaskMode => {
+                self.mask_mode = !self.mask_mode;
+                if self.mask_mode {
+                    self.mask_anchor = None;
+                    for universe in targets.iter_mut() {
+                        universe.mask = None;
+                    }
+                }
+            }
+            BoardEvent::Invert => {
+                targets.iter_mut().for_each(Universe::invert);
+            }
+            BoardEvent::Set { x, y } => {
+                let universe = &mut targets[0];
+                if universe.board.check_index((x, y)) && !universe.board[(x, y)].is_alive() {
+                    universe.board.index_mut((x, y)).flip();
+                    universe.edited();
+                }
+            }
+            BoardEvent::Rules(rules) => {
+                let universe = &mut targets[0];
+                universe.rules = rules;
+                universe.edited();
+            }
+            BoardEvent::Get(reply) => {
+                let _ = reply.send(to_rle_with_meta(&targets[0].board, &targets[0].rules, &targets[0].meta));
+            }
+            BoardEvent::HueCycle => {
+                self.hue_cycle = !self.hue_cycle;
+            }
+            #[cfg(feature = "serde")]
+            BoardEvent::SaveSession => {
+                let universe = &targets[0];
+                let theme = [(true, self.themes[self.theme_index].0), (self.hue_cycle, "hue"), (self.plain_palette, "plain"), (self.age_colors, "ages"), (self.blank_dead, "blank"), (self.trace, "trace")]
+                    .iter()
+                    .filter(|(on, _)| *on)
+                    .map(|(_, name)| *name)
+                    .collect::<Vec<_>>()
+                    .join("+");
+                let session = Session {
+                    board: universe.board.clone(),
+                    rules: universe.rules,
+                    generation: universe.generation,
+                    speed: self.speed,
+                    paused: self.paused,
+                    theme,
+                };
+                let path = self.args.session.clone().unwrap_or_else(|| PathBuf::from(DEFAULT_SESSION));
+                self.message = Some(match session.save(&path) {
+                    Ok(()) => format!("saved {}", path.display()),
+                    Err(err) => format!("error: {}", err),
+                });
+            }
+            #[cfg(not(feature = "serde"))]
+            BoardEvent::SaveSession => {
+                self.message = Some("saving sessions requires the `serde` feature".to_string());
+            }
+            BoardEvent::SaveCoords => {
+                let path = self.args.coords.clone().unwrap_or_else(|| PathBuf::from(DEFAULT_COORDS));
+                self.message = Some(match std::fs::write(&path, targets[0].board.save_coords()) {
+                    Ok(()) => format!("saved {}", path.display()),
+                    Err(err) => format!("error: {}", err),
+                });
+            }
+            BoardEvent::NextTheme => {
+                self.theme_index = (self.theme_index + 1) % self.themes.len();
+                self.message = Some(format!("theme {}", self.themes[self.theme_index].0));
+                self.clear = true;
+            }
+            BoardEvent::Classify => {
+                // runs ahead on a copy, the board itself is left where it is
+                let universe = &targets[0];
+                let motion = classify_motion(&mut universe.board.clone(), &universe.rules, CLASSIFY_PERIOD, CLASSIFY_GENERATIONS);
+                self.message = Some(match motion {
+                    Some(Motion::Spaceship { period, dx, dy }) => {
+                        format!("spaceship of period {} moving ({}, {})", period, dx, dy)
+                    }
+                    Some(Motion::Stationary(Cycle { start, period: 1 })) => format!("still after {} generations", start),
+                    Some(Motion::Stationary(Cycle { start, period })) => {
+                        format!("period {} after {} generations", period, start)
+                    }
+                    None => format!("no repeat within {} generations", CLASSIFY_GENERATIONS),
+                });
+            }
+            BoardEvent::Palette => {
+                self.plain_palette = !self.plain_palette;
+                self.clear = true;
+            }
+            BoardEvent::AgeColors => {
+                self.age_colors = !self.age_colors;
+            }
+            BoardEvent::BlankDead => {
+                self.blank_dead = !self.blank_dead;
+            }
+            BoardEvent::Trace => {
+                self.trace = !self.trace;
+            }
+            BoardEvent::ClearTrails => {
+                targets.iter_mut().for_each(Universe::clear_trails);
+            }
+            BoardEvent::Minimap => {
+                self.minimap = !self.minimap;
+                self.clear = true;
+            }
+            BoardEvent::FrameTimes => {
+                self.frame_times = match self.frame_times {
+                    Some(_) => None,
+                    None => Some(FrameTimes::default()),
+                };
+            }
+            BoardEvent::Edges => {
+                for universe in targets.iter_mut() {
+                    let edges = match universe.board.edges() {
+                        EdgeBehavior::Toroidal => EdgeBehavior::Bounded,
+                        EdgeBehavior::Bounded => EdgeBehavior::Klein,
+                        EdgeBehavior::Klein => EdgeBehavior::Projective,
+                        EdgeBehavior::Projective => EdgeBehavior::Toroidal,
+                    };
+                    universe.board.set_edges(edges);
+                    universe.edited();
+                }
+            }
+            BoardEvent::Symmetry => {
+                self.symmetry = self.symmetry.next();
+            }
+            BoardEvent::DrawMode(mode) => {
+                self.draw_mode = self.draw_mode.toggled(mode);
+                self.draw_anchor = None;
+            }
+            BoardEvent::StepBack => {
+                if targets.iter().all(|universe| universe.generation > 0) {
+                    for universe in targets.iter_mut() {
+                        universe.step_back();
+                    }
+                    self.paused = true;
+                    self.step_once = false;
+                }
+            }
+            BoardEvent::NewTab => {
+                let mut copy = Universe::new(targets[0].board.clone());
+                copy.rules = targets[0].rules;
+                copy.life_history = targets[0].life_history.clone();
+                self.universes.push(copy);
+                self.active = self.universes.len() - 1;
+            }
+            BoardEvent::NextTab => {
+                self.active = (self.active + 1) % self.universes.len();
+            }
+            BoardEvent::SelectTab(index) => {
+                if index < self.universes.len() {
+                    self.active = index;
+                }
+            }
+            BoardEvent::StepAll => {
+                self.step_all = !self.step_all;
+            }
+            BoardEvent::NextRules => {
+                // in split view the right half is the one being compared
+                if let Some(universe) = targets.last_mut() {
+                    universe.rules = next_preset(&universe.rules);
+                    universe.edited();
+                }
+            }
+            BoardEvent::Split => {
+                self.split = match self.split {
+                    Some(_) => None,
+                    None => {
+                        let mut left = Universe::new(self.universes[self.active].board.clone());
+                        left.rules = self.universes[self.active].rules;
+                        left.resize(split_width(self.columns, self.cell_columns, left.board.topology()) as usize, board_height(self.rows) as usize);
+                        let mut right = Universe::new(left.board.clone());
+                        right.rules = next_preset(&left.rules);
+                        Some([left, right])
+                    }
+                };
+                self.clear = true;
+            }
+            BoardEvent::Command => {
+                self.command = Some(String::new());
+                self.message = None;
+            }
+            BoardEvent::Input(c) => {
+                self.command.get_or_insert_with(String::new).push(c);
+            }
+            BoardEvent::Paste(text) => {
+                self.command.get_or_insert_with(String::new).push_str(&text);
+            }
+            BoardEvent::Backspace => {
+                if let Some(command) = &mut self.command {
+                    command.pop();
+                }
+            }
+            BoardEvent::Picker => {
+                self.picker = Some(0);
+                self.message = None;
+            }
+            BoardEvent::PickerMove(offset) => {
+                if let Some(selected) = &mut self.picker {
+                    *selected = (*selected as isize + offset).rem_euclid(self.catalog.len() as isize) as usize;
+                }
+            }
+            BoardEvent::Cancel => {
+                self.command = None;
+                if self.picker.take().is_some() {
+                    self.clear = true;
+                }
+            }
+            BoardEvent::Submit if self.picker.is_some() => {
+                let (_, pattern) = &self.catalog[self.picker.take().unwrap_or_default()];
+                for universe in targets.iter_mut() {
+                    universe.board.stamp(pattern, self.cursor_at);
+                    universe.edited();
+                }
+                self.clear = true;
+            }
+            BoardEvent::Submit => {
+                let text = self.command.take().unwrap_or_default();
+                self.message = match text.parse::<Pattern>() {
+                    Ok(pattern) => {
+                        for universe in targets.iter_mut() {
+                            universe.board.stamp(&pattern, self.cursor_at);
+                            universe.edited();
+                        }
+                        None
+                    }
+                    Err(error) => Some(format!("error: {}", error)),
+                };
+            }
+        }
+        true
+    }
+
+    // repeats of a held space bar only toggle once
+    fn toggle_pause(&mut self, now: Instant) -> bool {
+        let repeated = self.last_pause_key.is_some_and(|last| now.saturating_duration_since(last) < PAUSE_DEBOUNCE);
+        self.last_pause_key = Some(now);
+        if self.args.manual || repeated {
+            return false;
+        }
+        self.paused = !self.paused;
+        self.step_once = self.paused;
+        true
+    }
+
+    /// Steps the boards by the generations due at `now` and the ones asked for since the last tick.
+    pub fn tick(&mut self, now: Instant) -> Result<()> {
+        self.requested_steps = max(self.requested_steps, due_steps(self.paused, &mut self.step_once, &mut self.last_updated, now, self.speed));
+        let step_start = Instant::now();
+        let mut taken = 0;
+        while taken < self.requested_steps {
+            // a board too slow for its speed drops generations rather than holding up the next frame
+            if taken > 0 && step_start.elapsed() >= self.args.render_interval {
+                self.last_updated = Instant::now();
+                break;
+            }
+            taken += 1;
+            let stepped: &mut [Universe] = match &mut self.split {
+                Some(pair) => pair,
+                None if self.step_all => &mut self.universes,
+                None => std::slice::from_mut(&mut self.universes[self.active]),
+            };
+            stepped.iter_mut().for_each(Universe::step);
+            if self.args.reseed_on_death {
+                for universe in stepped.iter_mut().filter(|universe| universe.stagnant(self.args.reseed_after)) {
+                    self.reseeds += 1;
+                    universe.reseed(self.args.seed.unwrap_or_default().wrapping_add(self.reseeds), self.args.soup);
+                }
+            }
+            let stepped = match &self.split {
+                Some(pair) => &pair[0],
+                None => &self.universes[self.active],
+            };
+            if let Some(out) = &mut self.record {
+                record_text(out, &stepped.board, stepped.generation)?;
+                if stepped.generation % RECORD_FLUSH_INTERVAL == 0 {
+                    out.flush()?;
+                }
+            }
+            if let Some(out) = &mut self.csv {
+                log_csv(out, &stepped.board, stepped.generation)?;
+            }
+            if self.args.max_gen.is_some_and(|max_gen| stepped.generation >= max_gen) {
+                break;
+            }
+        }
+        if let Some(frame_times) = self.frame_times.as_mut().filter(|_| taken > 0) {
+            frame_times.step.record(step_start.elapsed() / taken);
+        }
+        self.requested_steps = 0;
+        Ok(())
+    }
+
+    /// Whether the generation limit of `--max-gen` is reached.
+    pub fn finished(&self) -> bool {
+        self.args.max_gen.is_some_and(|max_gen| self.shown().generation >= max_gen)
+    }
+
+    pub fn stats(&self) -> RunStats {
+        RunStats { seed: self.args.seed, ..self.shown().stats }
+    }
+
+    // the universe on the left in split view, the active tab otherwise
+    fn shown(&self) -> &Universe {
+        match &self.split {
+            Some(pair) => &pair[0],
+            None => &self.universes[self.active],
+        }
+    }
+}
+
+/// Appends `board` as plain text under a `--- gen N ---` header.
+fn record_text(out: &mut impl Write, board: &Board, generation: u64) -> Result<()> {
+    let mut ascii = String::new();
+    board.write_ascii(&mut ascii).expect("writing to a string cannot fail");
+    write!(out, "--- gen {} ---\n{}", generation, ascii)
+}
+
+/// Appends a `generation,population,births,deaths` line and flushes it, so a killed run
+/// still leaves every line it got to.
+fn log_csv(out: &mut impl Write, board: &Board, generation: u64) -> Result<()> {
+    let stats = board.step_stats();
+    writeln!(out, "{},{},{},{}", generation, stats.population, stats.births, stats.deaths)?;
+    out.flush()
+}
+
+/// Summary of a universe's run, printed on exit.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct RunStats {
+    pub generations: u64,
+    pub peak_population: usize,
+    pub peak_generation: u64,
+    pub final_population: usize,
+    pub stabilized: bool,
+    // of the random soup the run started from, printed so that it can be run again
+    pub seed: Option<u64>,
+}
+
+impl RunStats {
+    fn new(board: &Board) -> Self {
+        let population = board.live_count();
+        RunStats {
+            peak_population: population,
+            final_population: population,
+            ..RunStats::default()
+        }
+    }
+
+    fn record(&mut self, generation: u64, population: usize, changed: bool) {
+        self.generations += 1;
+        if population > self.peak_population {
+            self.peak_population = population;
+            self.peak_generation = generation;
+        }
+        self.final_population = population;
+        self.stabilized = !changed;
+    }
+}
+
+impl Display for RunStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "generations: {}", self.generations)?;
+        writeln!(f, "peak population: {} (generation {})", self.peak_population, self.peak_generation)?;
+        writeln!(f, "final population: {}", self.final_population)?;
+        write!(f, "stabilized: {}", if self.stabilized { "yes" } else { "no" })?;
+        match self.seed {
+            Some(seed) => write!(f, "\nseed: {}", seed),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A board with its own generation counter, rules and rewind history.
+pub struct Universe {
+    board: Board,
+    generation: u64,
+    history: History,
+    rules: Rules,
+    rewound: bool,
+    stats: RunStats,
+    // generations in a row the board did not change in
+    unchanged_for: u64,
+    // generations each cell has spent alive or dead since it last changed, row-major like the board,
+    // cells that were dead before the last edit count as dead forever
+    ages: Vec<u32>,
+    // cells that have been alive since the trails were last cleared, row-major like the board
+    visited: Vec<bool>,
+    // only cells inside are simulated
+    mask: Option<Mask>,
+    // LifeHistory states of patterns loaded with them
+    life_history: Option<HistoryLayer>,
+    // the `#` lines of the pattern the board started from, written back by `get`
+    meta: PatternMeta,
+    // a one-dimensional automaton writing each generation into the row below the last one
+    rule1d: Option<Rule1D>,
+}
+
+impl Universe {
+    pub fn new(board: Board) -> Self {
+        let mut history = History::new(KEYFRAME_INTERVAL, KEYFRAME_CAPACITY);
+        history.keyframe(0, &board);
+        let mut universe = Universe {
+            stats: RunStats::new(&board),
+            unchanged_for: 0,
+            ages: vec![0; board.width() * board.height()],
+            visited: vec![false; board.width() * board.height()],
+            board,
+            generation: 0,
+            history,
+            rules: Rules::conway(),
+            rewound: false,
+            mask: None,
+            life_history: None,
+            meta: PatternMeta::default(),
+            rule1d: None,
+        };
+        universe.sync_ages();
+        universe.sync_visited();
+        universe
+    }
+
+    pub fn step(&mut self) {
+        let changed = match (self.rule1d, &self.mask) {
+            (Some(rule), _) => {
+                let before = self.board.clone();
+                step_1d(&mut self.board, rule, self.generation as usize);
+                self.board != before
+            }
+            (None, Some(mask)) => next_state_masked(&mut self.board, &self.rules.emulated(self.generation), mask),
+            (None, None) => next_state_with(&mut self.board, &self.rules.emulated(self.generation)),
+        };
+        self.generation += 1;
+        self.stats.record(self.generation, self.board.live_count(), changed);
+        self.unchanged_for = if changed { 0 } else { self.unchanged_for + 1 };
+        self.history.record(self.generation, &self.board);
+        self.rewound = false;
+        self.sync_life_history();
+        let width = self.board.width();
+        for (index, age) in self.ages.iter_mut().enumerate() {
+            let cell = self.board[(index % width, index / width)];
+            *age = match cell {
+                Cell::Alive | Cell::Dead => age.saturating_add(1),
+                Cell::Born | Cell::Died => 0,
+            };
+            self.visited[index] |= cell.is_alive();
+        }
+    }
+
+    pub fn step_back(&mut self) -> bool {
+        // rows scrolled off the top cannot be replayed with the 2D rules the history knows
+        if self.generation == 0 || self.rule1d.is_some() {
+            return false;
+        }
+        match self.history.reconstruct(self.generation - 1, &self.rules) {
+            Some(previous) => {
+                self.board = previous;
+                self.generation -= 1;
+                self.rewound = true;
+                self.sync_ages();
+                self.sync_visited();
+                self.sync_life_history();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Must be called after the board or rules were changed outside of stepping.
+    pub fn edited(&mut self) {
+        self.unchanged_for = 0;
+        self.history.keyframe(self.generation, &self.board);
+        self.rewound = false;
+        self.sync_ages();
+        self.sync_visited();
+        self.sync_life_history();
+    }
+
+    /// Whether the board died out, or has not changed for `after` generations.
+    fn stagnant(&self, after: u64) -> bool {
+        self.stats.final_population == 0 || self.unchanged_for >= after
+    }
+
+    /// Replaces the cells with a random soup, keeping the size, edges and shape of the board.
+    pub fn reseed(&mut self, seed: u64, symmetry: Symmetry) {
+        let soup = Board::random_symmetric(self.board.width(), self.board.height(), SOUP_DENSITY, seed, symmetry);
+        self.board.map_in_place(|_, index| soup[index]);
+        self.edited();
+    }
+
+    /// Turns every live cell dead and the other way around, without any died or born highlights.
+    pub fn invert(&mut self) {
+        self.board.map_in_place(|cell, _| if cell.is_alive() { Cell::Dead } else { Cell::Alive });
+        // every cell changed, so no age or fade carries over
+        self.ages.fill(u32::MAX);
+        self.edited();
+    }
+
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let old_width = self.board.width();
+        let mut ages = vec![u32::MAX; width * height];
+        for (index, &age) in self.ages.iter().enumerate() {
+            let (x, y) = (index % old_width, index / old_width);
+            if x < width && y < height {
+                ages[y * width + x] = age;
+            }
+        }
+        self.ages = ages;
+        let mut visited = vec![false; width * height];
+        for (index, &cell) in self.visited.iter().enumerate() {
+            let (x, y) = (index % old_width, index / old_width);
+            if x < width && y < height {
+                visited[y * width + x] = cell;
+            }
+        }
+        self.visited = visited;
+        self.mask = self.mask.take().map(|mask| mask.cropped(width, height));
+        self.life_history = self.life_history.take().map(|old| {
+            let mut layer = HistoryLayer::new(width, height);
+            layer.stamp(&old, (0, 0));
+            layer
+        });
+        resize(&mut self.board, width, height);
+        self.history.clear();
+        self.edited();
+    }
+
+    /// Forgets where live cells have been, except where they are now.
+    pub fn clear_trails(&mut self) {
+        self.visited.fill(false);
+        self.sync_visited();
+    }
+
+    fn sync_life_history(&mut self) {
+        if let Some(life_history) = &mut self.life_history {
+            life_history.update(&self.board);
+        }
+    }
+
+    fn sync_ages(&mut self) {
+        self.ages.resize(self.board.width() * self.board.height(), u32::MAX);
+        let width = self.board.width();
+        for (index, age) in self.ages.iter_mut().enumerate() {
+            match self.board[(index % width, index / width)] {
+                Cell::Alive | Cell::Born if *age == u32::MAX => *age = 0,
+                Cell::Alive | Cell::Born => {}
+                Cell::Died => *age = 0,
+                Cell::Dead => *age = u32::MAX,
+            }
+        }
+    }
+
+    fn sync_visited(&mut self) {
+        self.visited.resize(self.board.width() * self.board.height(), false);
+        let width = self.board.width();
+        for entry in self.board.iter().filter(|entry| entry.cell().is_alive()) {
+            self.visited[entry.y() * width + entry.x()] = true;
+        }
+    }
+}
+
+const FRAME_TIME_WINDOW: usize = 32;
+
+/// Last duration and the average over the most recent `FRAME_TIME_WINDOW` ones.
+#[derive(Debug, Default, Clone)]
+struct RollingAverage {
+    samples: VecDeque<Duration>,
+    total: Duration,
+}
+
+impl RollingAverage {
+    fn record(&mut self, sample: Duration) {
+        if self.samples.len() == FRAME_TIME_WINDOW {
+            self.total -= self.samples.pop_front().unwrap_or_default();
+        }
+        self.samples.push_back(sample);
+        self.total += sample;
+    }
+
+    fn last(&self) -> Duration {
+        self.samples.back().copied().unwrap_or_default()
+    }
+
+    fn average(&self) -> Duration {
+        match self.samples.len() {
+            0 => Duration::ZERO,
+            len => self.total / len as u32,
+        }
+    }
+}
+
+/// How long stepping and drawing took, shown in the status bar for performance debugging.
+#[derive(Debug, Default, Clone)]
+struct FrameTimes {
+    step: RollingAverage,
+    draw: RollingAverage,
+}
+
+impl Display for FrameTimes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        write!(
+            f,
+            "step {:.2}ms (avg {:.2}) draw {:.2}ms (avg {:.2})",
+            ms(self.step.last()),
+            ms(self.step.average()),
+            ms(self.draw.last()),
+            ms(self.draw.average()),
+        )
+    }
+}
+
+/// What clicking on the board draws, shapes other than points take a start and an end click.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DrawMode {
+    Point,
+    Line,
+    Rect,
+    RectOutline,
+}
+
+impl DrawMode {
+    /// Mode after pressing the key of `mode`: it is switched on, the rectangle key then
+    /// switches to outlines, and pressing the key of the current mode goes back to points.
+    fn toggled(self, mode: DrawMode) -> Self {
+        match (self, mode) {
+            (DrawMode::Rect, DrawMode::Rect) => DrawMode::RectOutline,
+            (DrawMode::RectOutline, DrawMode::Rect) => DrawMode::Point,
+            (current, mode) if current == mode => DrawMode::Point,
+            (_, mode) => mode,
+        }
+    }
+
+    /// Cells of the shape spanned from `start` to `end`, lines are rasterized with Bresenham's algorithm.
+    pub fn cells(self, start: (usize, usize), end: (usize, usize)) -> Vec<(usize, usize)> {
+        let (left, right) = (min(start.0, end.0), max(start.0, end.0));
+        let (top, bottom) = (min(start.1, end.1), max(start.1, end.1));
+        match self {
+            DrawMode::Point => vec![end],
+            DrawMode::Line => line_cells(start, end),
+            DrawMode::Rect => (top..=bottom)
+                .flat_map(|y| (left..=right).map(move |x| (x, y)))
+                .collect(),
+            DrawMode::RectOutline => (top..=bottom)
+                .flat_map(|y| (left..=right).map(move |x| (x, y)))
+                .filter(|&(x, y)| x == left || x == right || y == top || y == bottom)
+                .collect(),
+        }
+    }
+}
+
+impl Display for DrawMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DrawMode::Point => write!(f, "point"),
+            DrawMode::Line => write!(f, "line"),
+            DrawMode::Rect => write!(f, "rectangle"),
+            DrawMode::RectOutline => write!(f, "outline"),
+        }
+    }
+}
+
+fn line_cells((x0, y0): (usize, usize), (x1, y1): (usize, usize)) -> Vec<(usize, usize)> {
+    let (x1, y1) = (x1 as isize, y1 as isize);
+    let (mut x, mut y) = (x0 as isize, y0 as isize);
+    let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+    let (sx, sy) = ((x1 - x).signum(), (y1 - y).signum());
+    let mut error = dx + dy;
+    let mut cells = vec![(x as usize, y as usize)];
+    while (x, y) != (x1, y1) {
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += sx;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += sy;
+        }
+        cells.push((x as usize, y as usize));
+    }
+    cells
+}
+
+/// Generations due at `now` at `speed` generations per second, `last_updated` moves on by as
+/// many. Nothing is due while paused, whatever else happened in the meantime, except once
+/// when `step_once` is set, and the time spent paused is not caught up on afterwards.
+fn due_steps(paused: bool, step_once: &mut bool, last_updated: &mut Instant, now: Instant, speed: f64) -> u32 {
+    if paused && !std::mem::take(step_once) {
+        *last_updated = now;
+        return 0;
+    }
+    let due = (now.saturating_duration_since(*last_updated).as_secs_f64() * speed) as u32;
+    *last_updated += Duration::from_secs_f64(1.0 / speed) * due;
+    due
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    use crossterm::style::Color;
+
+    use crossterm::style::{ContentStyle, Stylize};
+
+    use game_of_life::board::{Board, Cell};
+    use game_of_life::elementary::Rule1D;
+    use game_of_life::pattern::Pattern;
+    use game_of_life::grid::Topology;
+    use game_of_life::life_history::{HistoryLayer, HistoryState};
+    use game_of_life::mask::Mask;
+
+    use crate::args::Args;
+
+    use super::{AppState, Area, board_at, board_height, board_width, BoardEvent, ColorSupport, DEFAULT_THEME, DrawMode, due_steps, handle_event, hsv_to_rgb, InputMode, line_cells, log_csv, minimap, nearest_ansi16, RollingAverage, RunStats, ramp_speed, split_width, Theme, Universe, THEMES, usable_size, screen_len, DEFAULT_SPEED, FALLBACK_SIZE, FRAME_TIME_WINDOW, MAX_SPEED, MIN_SPEED, SPEED_STEP};
+
+    #[test]
+    fn command_line_input() {
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        let mut mode = InputMode::Board;
+        assert!(matches!(handle_event(key(KeyCode::Char('s')), &mut mode), Some(BoardEvent::Step)));
+        assert!(matches!(handle_event(key(KeyCode::Char(':')), &mut mode), Some(BoardEvent::Command)));
+        assert_eq!(mode, InputMode::Command);
+        assert!(matches!(handle_event(key(KeyCode::Char('s')), &mut mode), Some(BoardEvent::Input('s'))));
+        assert!(matches!(handle_event(key(KeyCode::Enter), &mut mode), Some(BoardEvent::Submit)));
+        assert_eq!(mode, InputMode::Board);
+        assert!(matches!(handle_event(key(KeyCode::Char('s')), &mut mode), Some(BoardEvent::Step)));
+    }
+
+    #[test]
+    fn ctrl_c_exits_from_any_mode() {
+        let ctrl_c = Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        for mut mode in [InputMode::Board, InputMode::Command, InputMode::Picker] {
+            assert!(matches!(handle_event(ctrl_c.clone(), &mut mode), Some(BoardEvent::Exit)));
+        }
+        let c = Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE));
+        assert!(matches!(handle_event(c, &mut InputMode::Board), Some(BoardEvent::Classify)));
+    }
+
+    #[test]
+    fn queued_shortcuts_stay_shortcuts() {
+        // only bracketed paste opens the command line, keys typed in quick succession do not
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        let mut mode = InputMode::Board;
+        assert!(matches!(handle_event(key(KeyCode::Char('q')), &mut mode), Some(BoardEvent::Exit)));
+        assert!(matches!(handle_event(key(KeyCode::Char('q')), &mut mode), Some(BoardEvent::Exit)));
+        assert_eq!(mode, InputMode::Board);
+        assert!(matches!(handle_event(Event::Paste("boq".to_string()), &mut mode), Some(BoardEvent::Paste(_))));
+        assert_eq!(mode, InputMode::Command);
+    }
+
+    #[test]
+    fn picker_consumes_keys() {
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        let mut mode = InputMode::Board;
+        assert!(matches!(handle_event(key(KeyCode::Char('p')), &mut mode), Some(BoardEvent::Picker)));
+        assert_eq!(mode, InputMode::Picker);
+        assert!(matches!(handle_event(key(KeyCode::Down), &mut mode), Some(BoardEvent::PickerMove(1))));
+        assert!(matches!(handle_event(key(KeyCode::Up), &mut mode), Some(BoardEvent::PickerMove(-1))));
+        assert!(handle_event(key(KeyCode::Char('s')), &mut mode).is_none());
+        assert!(handle_event(key(KeyCode::Char('q')), &mut mode).is_none());
+        assert!(matches!(handle_event(key(KeyCode::Char('p')), &mut mode), Some(BoardEvent::Cancel)));
+        assert_eq!(mode, InputMode::Board);
+        handle_event(key(KeyCode::Char('p')), &mut mode);
+        assert!(matches!(handle_event(key(KeyCode::Enter), &mut mode), Some(BoardEvent::Submit)));
+        assert_eq!(mode, InputMode::Board);
+    }
+
+    #[test]
+    fn hovering_finds_the_cell() {
+        let moved = Event::Mouse(MouseEvent { kind: MouseEventKind::Moved, column: 7, row: 2, modifiers: KeyModifiers::NONE });
+        assert!(matches!(handle_event(moved, &mut InputMode::Board), Some(BoardEvent::Hover { x: 7, y: 2 })));
+
+        // two 3 cells wide boards of 2 columns per cell, with the separator at column 6
+        let pair = [Universe::new(Board::new(3, 3)), Universe::new(Board::new(3, 3))];
+        assert_eq!(board_at(&pair, (5, 1), 2), (0, (2, 1)));
+        assert_eq!(board_at(&pair, (7, 2), 2), (1, (0, 2)));
+        assert_eq!(board_at(&pair[..1], (7, 2), 2), (0, (3, 2)));
+    }
+
+    #[test]
+    fn hexagon_rows_are_offset() {
+        let mut board = Board::new(3, 2);
+        board.set_topology(Topology::Hex);
+        let area = Area { x: 1, ..Area::of(&board) };
+        assert_eq!(area.position((1, 0), 2), (5, 0));
+        assert_eq!(area.position((1, 1), 2), (7, 1));
+        let hex = [Universe::new(board)];
+        assert_eq!(board_at(&hex, (4, 0), 2), (0, (1, 0)));
+        assert_eq!(board_at(&hex, (6, 1), 2), (0, (1, 1)));
+        assert_eq!(board_at(&hex, (0, 1), 2), (0, (0, 1)));
+        assert_eq!(board_width(80, 2, Topology::Hex), 20);
+        assert_eq!(split_width(81, 1, Topology::Hex), 20);
+    }
+
+    #[test]
+    fn huge_boards_are_clipped_to_the_screen() {
+        let board = Board::new(70_000, 3);
+        let area = Area { x: 4, y: 1, ..Area::of(&board) };
+        assert_eq!((area.width, area.height), (u16::MAX, 3));
+        assert_eq!(screen_len(usize::MAX), u16::MAX);
+        assert_eq!(area.position((2, 2), 2), (8, 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the terminal")]
+    #[cfg(debug_assertions)]
+    fn positions_beyond_the_terminal_are_caught() {
+        let area = Area { x: 0, y: 0, width: 10, height: 10, topology: Topology::Square };
+        area.position((40_000, 0), 2);
+    }
+
+    #[test]
+    fn resizing_while_paused_takes_no_step() {
+        let mut board = Board::from_live_coords(5, 5, [(1, 2), (2, 2), (3, 2)]).unwrap();
+        board.step();
+        let before = board.clone();
+        let mut universe = Universe::new(board);
+        let (mut step_once, start) = (false, Instant::now());
+        let mut last_updated = start;
+        universe.resize(7, 6);
+        assert_eq!(due_steps(true, &mut step_once, &mut last_updated, start + Duration::from_secs(10), 15.0), 0);
+        assert_eq!(universe.generation, 0);
+        assert!(before.iter().all(|entry| universe.board[entry.index()] == entry.cell()));
+
+        // resuming starts over from the time of the last paused frame instead of catching up
+        assert_eq!(due_steps(false, &mut step_once, &mut last_updated, start + Duration::from_millis(10_100), 15.0), 1);
+        // the frame pausing is pressed in still steps, the ones after it don't
+        step_once = true;
+        assert_eq!(due_steps(true, &mut step_once, &mut last_updated, start + Duration::from_millis(10_500), 15.0), 6);
+        assert!(!step_once);
+        assert_eq!(due_steps(true, &mut step_once, &mut last_updated, start + Duration::from_secs(12), 15.0), 0);
+    }
+
+    #[test]
+    fn pausing_then_stepping_takes_one_generation() {
+        let board = Board::from_live_coords(5, 5, [(1, 2), (2, 2), (3, 2)]).unwrap();
+        let mut state = AppState::new(Args::default(), Universe::new(board), THEMES.clone(), (60, 15), 1);
+        let start = Instant::now();
+        state.tick(start + Duration::from_secs(1)).unwrap();
+        assert_eq!(state.shown().generation, 15);
+
+        assert!(state.apply(BoardEvent::Pause));
+        // the frame pausing is pressed in still takes what is due
+        state.tick(start + Duration::from_secs(1)).unwrap();
+        state.tick(start + Duration::from_secs(10)).unwrap();
+        assert_eq!(state.shown().generation, 15);
+        state.apply(BoardEvent::Step);
+        state.tick(start + Duration::from_secs(20)).unwrap();
+        state.tick(start + Duration::from_secs(30)).unwrap();
+        assert_eq!(state.shown().generation, 16);
+
+        state.apply(BoardEvent::Speed(true));
+        assert!(state.speed > DEFAULT_SPEED);
+        assert!(!state.exit);
+        state.apply(BoardEvent::Exit);
+        assert!(state.exit);
+    }
+
+    #[test]
+    fn held_space_toggles_once() {
+        let mut state = AppState::new(Args::default(), Universe::new(Board::new(5, 5)), THEMES.clone(), (60, 15), 1);
+        let start = Instant::now();
+        assert!(state.toggle_pause(start));
+        assert!(state.paused && state.step_once);
+        // key repeat keeps arriving faster than the debounce for as long as space is held
+        for repeat in 1..10 {
+            assert!(!state.toggle_pause(start + Duration::from_millis(30 * repeat)));
+        }
+        assert!(state.paused);
+        assert!(state.toggle_pause(start + Duration::from_secs(1)));
+        assert!(!state.paused && !state.step_once);
+    }
+
+    #[test]
+    fn hsv_primaries() {
+        assert_eq!(hsv_to_rgb(0.0, 1.0, 1.0), (255, 0, 0));
+        assert_eq!(hsv_to_rgb(1.0 / 3.0, 1.0, 1.0), (0, 255, 0));
+        assert_eq!(hsv_to_rgb(2.0 / 3.0, 1.0, 1.0), (0, 0, 255));
+        assert_eq!(hsv_to_rgb(1.0, 1.0, 1.0), (255, 0, 0));
+        assert_eq!(hsv_to_rgb(0.5, 0.0, 1.0), (255, 255, 255));
+    }
+
+    #[test]
+    fn snaps_to_ansi() {
+        assert_eq!(nearest_ansi16(Color::Rgb { r: 250, g: 10, b: 10 }), Color::Red);
+        assert_eq!(nearest_ansi16(Color::Rgb { r: 120, g: 130, b: 10 }), Color::DarkYellow);
+        assert_eq!(nearest_ansi16(Color::Green), Color::Green);
+    }
+
+    #[test]
+    fn color_support_from_colorterm() {
+        assert_eq!(ColorSupport::from_colorterm(Some("truecolor")), ColorSupport::TrueColor);
+        assert_eq!(ColorSupport::from_colorterm(Some("24bit")), ColorSupport::TrueColor);
+        assert_eq!(ColorSupport::from_colorterm(Some("")), ColorSupport::Ansi16);
+        assert_eq!(ColorSupport::from_colorterm(None), ColorSupport::Ansi16);
+    }
+
+    #[test]
+    fn speed_ramp() {
+        let faster = ramp_speed(DEFAULT_SPEED, true);
+        assert!((faster / DEFAULT_SPEED - SPEED_STEP).abs() < 1e-9);
+        assert!((ramp_speed(faster, false) - DEFAULT_SPEED).abs() < 1e-9);
+        assert_eq!(ramp_speed(MAX_SPEED, true), MAX_SPEED);
+        assert_eq!(ramp_speed(MIN_SPEED, false), MIN_SPEED);
+    }
+
+    #[test]
+    fn wide_glyphs() {
+        assert_eq!(DEFAULT_THEME.cell_columns(), 1);
+        let glyphs = |dead: &str, alive: &str| Theme::new(
+            ContentStyle::new(), ContentStyle::new(), ContentStyle::new(), ContentStyle::new(),
+            dead.to_string(), alive.to_string(), dead.to_string(), alive.to_string(),
+        ).map(|theme| theme.cell_columns());
+        assert_eq!(glyphs("生", "生"), Ok(2));
+        assert_eq!(glyphs("🟩", "🟩"), Ok(2));
+        assert_eq!(glyphs("##", "##"), Ok(2));
+        assert_eq!(glyphs("·", "█"), Ok(1));
+        assert!(glyphs("###", "###").is_err());
+        assert!(glyphs("", "").is_err());
+        assert!(glyphs("\u{301}", "█").is_err());
+        assert_eq!(glyphs("█", "██"), Err("`█` takes 1 columns but `██` takes 2".to_string()));
+        assert!(glyphs("🟩", "█").is_err());
+    }
+
+    #[test]
+    fn degrade_theme() {
+        let mut theme = DEFAULT_THEME.clone();
+        theme.born_cell_style = ContentStyle::new().with(Color::Rgb { r: 10, g: 200, b: 20 }).on(Color::Rgb { r: 5, g: 5, b: 5 });
+        let degraded = theme.degrade_to_ansi16();
+        assert_eq!(degraded.born_cell_style.foreground_color, Some(Color::Green));
+        assert_eq!(degraded.born_cell_style.background_color, Some(Color::Black));
+        assert_eq!(degraded.alive_cell_style, theme.alive_cell_style);
+    }
+
+    #[test]
+    fn ages_follow_live_cells() {
+        let board = Board::from_live_coords(6, 6, [(1, 2), (2, 2), (3, 2)]).unwrap();
+        let mut universe = Universe::new(board);
+        universe.step();
+        universe.step();
+        // the blinker's center has been alive all along, its tips keep being reborn
+        assert_eq!(universe.ages[2 * 6 + 2], 2);
+        assert_eq!(universe.ages[2 * 6 + 1], 0);
+        assert_eq!(universe.ages[6 + 2], 0);
+        universe.resize(3, 3);
+        assert_eq!(universe.ages.len(), 9);
+        assert_eq!(universe.ages[2 * 3 + 2], 2);
+        universe.board = Board::new(3, 3);
+        universe.edited();
+        assert!(universe.ages.iter().all(|&age| age == u32::MAX));
+    }
+
+    #[test]
+    fn csv_lines_follow_the_board() {
+        let mut universe = Universe::new(Board::from_live_coords(5, 5, [(1, 2), (2, 2), (3, 2)]).unwrap());
+        let mut out = Vec::new();
+        log_csv(&mut out, &universe.board, universe.generation).unwrap();
+        universe.step();
+        log_csv(&mut out, &universe.board, universe.generation).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "0,3,0,0\n1,3,2,2\n");
+    }
+
+    #[test]
+    fn minimap_blocks() {
+        let mut board = Board::new(12, 8);
+        board.stamp(&Pattern::glider(), (8, 4));
+        let pixels = minimap(&board, (4, 4));
+        let alive: Vec<_> = (0..16).filter(|&index| pixels[index / 4][index % 4]).collect();
+        // blocks of 3x2 cells, any live cell lights up its block
+        assert_eq!(alive, [11, 14, 15]);
+        // blocks are never empty, so a narrow board has fewer of them
+        assert_eq!(minimap(&Board::new(5, 3), (4, 10)).len(), 3);
+        assert_eq!(minimap(&Board::new(5, 3), (4, 10))[0].len(), 3);
+    }
+
+    #[test]
+    fn screensaver_reseeds_dead_and_still_boards() {
+        let args = Args::parse(["--screensaver", "--reseed-after", "3", "--manual"].map(String::from)).unwrap();
+        // a lone cell dies in the first generation, a block never changes
+        let lone = Board::from_live_coords(10, 10, [(4, 4)]).unwrap();
+        let mut state = AppState::new(args.clone(), Universe::new(lone), THEMES.clone(), (60, 15), 1);
+        state.apply(BoardEvent::Step);
+        state.tick(Instant::now()).unwrap();
+        assert!(state.shown().board.live_count() > 0);
+        assert_eq!(state.reseeds, 1);
+
+        let block = Board::from_live_coords(10, 10, [(1, 1), (2, 1), (1, 2), (2, 2)]).unwrap();
+        let mut state = AppState::new(args, Universe::new(block.clone()), THEMES.clone(), (60, 15), 1);
+        for _ in 0..2 {
+            state.apply(BoardEvent::Step);
+            state.tick(Instant::now()).unwrap();
+        }
+        assert_eq!(state.shown().board.to_string(), block.to_string());
+        state.apply(BoardEvent::Step);
+        state.tick(Instant::now()).unwrap();
+        assert_ne!(state.shown().board.to_string(), block.to_string());
+    }
+
+    #[test]
+    fn masks_survive_resizing() {
+        let mut universe = Universe::new(Board::new(8, 8));
+        universe.mask = Some(Mask::rect(8, 8, (1, 1), (6, 6)));
+        universe.resize(5, 10);
+        assert_eq!(universe.mask, Some(Mask::rect(5, 10, (1, 1), (4, 6))));
+    }
+
+    #[test]
+    fn life_history_survives_resizing() {
+        let mut universe = Universe::new(Board::new(6, 6));
+        let mut layer = HistoryLayer::new(6, 6);
+        layer.set((1, 1), HistoryState::MarkedOff);
+        layer.set((4, 4), HistoryState::Boundary);
+        universe.life_history = Some(layer);
+        universe.resize(3, 8);
+        let layer = universe.life_history.as_ref().unwrap();
+        assert_eq!((layer.width(), layer.height()), (3, 8));
+        assert_eq!(layer.get((1, 1)), HistoryState::MarkedOff);
+        assert_eq!(layer.get((4, 4)), HistoryState::Off);
+        // stepping updates the layer, which has to be of the board's size
+        universe.step();
+    }
+
+    #[test]
+    fn trails_stay_until_cleared() {
+        let board = Board::from_live_coords(5, 5, [(1, 2), (2, 2), (3, 2)]).unwrap();
+        let mut universe = Universe::new(board);
+        universe.step();
+        // the blinker stood up, its old tips are dead but keep their trail
+        let trails: Vec<_> = (0..25).filter(|&index| universe.visited[index]).collect();
+        assert_eq!(trails, [7, 11, 12, 13, 17]);
+        universe.resize(4, 4);
+        assert_eq!(universe.visited.iter().filter(|&&visited| visited).count(), 5);
+        universe.clear_trails();
+        assert!(!universe.visited[2 * 4 + 1]);
+        assert!(universe.visited[4 + 2] && universe.visited[2 * 4 + 2]);
+    }
+
+    #[test]
+    fn rule1d_writes_rows_downwards() {
+        let board = Board::from_live_coords(5, 3, [(2, 0)]).unwrap();
+        let mut universe = Universe::new(board);
+        universe.rule1d = Some(Rule1D(90));
+        universe.step();
+        assert_eq!(universe.board.to_string(), "XXOXX\nXOXOX\nXXXXX\n");
+        assert!(!universe.step_back());
+    }
+
+    #[test]
+    fn age_style_saturates() {
+        let theme = DEFAULT_THEME.clone();
+        assert_eq!(theme.age_style(0).foreground_color, Some(Color::Rgb { r: 0, g: 255, b: 0 }));
+        assert_eq!(theme.age_style(u32::MAX).foreground_color, Some(Color::Rgb { r: 255, g: 0, b: 0 }));
+    }
+
+    #[test]
+    fn themes() {
+        assert_eq!(THEMES[0].0, "lifecycle");
+        for (index, (name, theme)) in THEMES.iter().enumerate() {
+            assert_eq!(theme.cell_columns(), 1, "{}", name);
+            assert!(THEMES[..index].iter().all(|(other, _)| other != name));
+        }
+        let heatmap = &THEMES.iter().find(|(name, _)| *name == "heatmap").unwrap().1;
+        assert!(heatmap.heat);
+        assert_ne!(heatmap.age_style(0), heatmap.age_style(u32::MAX));
+    }
+
+    #[test]
+    fn drawn_shapes() {
+        assert_eq!(DrawMode::Line.cells((0, 0), (4, 2)), vec![(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]);
+        assert_eq!(DrawMode::Line.cells((2, 3), (2, 0)), vec![(2, 3), (2, 2), (2, 1), (2, 0)]);
+        assert_eq!(DrawMode::Line.cells((1, 1), (1, 1)), vec![(1, 1)]);
+        assert_eq!(DrawMode::Rect.cells((2, 1), (0, 0)).len(), 6);
+        let outline = DrawMode::RectOutline.cells((0, 0), (2, 2));
+        assert_eq!(outline.len(), 8);
+        assert!(!outline.contains(&(1, 1)));
+        assert_eq!(DrawMode::Point.toggled(DrawMode::Rect), DrawMode::Rect);
+        assert_eq!(DrawMode::Rect.toggled(DrawMode::Rect), DrawMode::RectOutline);
+        assert_eq!(DrawMode::RectOutline.toggled(DrawMode::Rect), DrawMode::Point);
+        assert_eq!(DrawMode::Line.toggled(DrawMode::Line), DrawMode::Point);
+        assert_eq!(DrawMode::Rect.toggled(DrawMode::Line), DrawMode::Line);
+    }
+
+    #[test]
+    fn shift_click_draws_a_line_from_the_last_click() {
+        let shift_click = Event::Mouse(MouseEvent { kind: MouseEventKind::Down(MouseButton::Left), column: 5, row: 3, modifiers: KeyModifiers::SHIFT });
+        assert!(matches!(handle_event(shift_click, &mut InputMode::Board), Some(BoardEvent::MouseClick { x: 5, y: 3, drag: false, shift: true })));
+
+        let mut state = AppState::new(Args::default(), Universe::new(Board::new(8, 8)), THEMES.clone(), (60, 15), 1);
+        // without an earlier click there is no line to draw, only the cell is toggled
+        state.apply(BoardEvent::MouseClick { x: 1, y: 1, drag: false, shift: true });
+        state.apply(BoardEvent::MouseClick { x: 5, y: 3, drag: false, shift: true });
+        let mut live: Vec<_> = state.shown().board.iter().filter(|entry| entry.cell().is_alive()).map(|entry| entry.index()).collect();
+        let mut expected = line_cells((1, 1), (5, 3));
+        live.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(live, expected);
+        state.apply(BoardEvent::MouseClick { x: 5, y: 3, drag: false, shift: false });
+        assert!(!state.shown().board[(5, 3)].is_alive());
+    }
+
+    #[test]
+    fn zero_sized_terminals_use_the_fallback() {
+        assert_eq!(usable_size(Some((0, 0))), FALLBACK_SIZE);
+        assert_eq!(usable_size(Some((120, 0))), FALLBACK_SIZE);
+        assert_eq!(usable_size(None), FALLBACK_SIZE);
+        assert_eq!(usable_size(Some((3, 2))), (3, 2));
+    }
+
+    #[test]
+    fn thin_terminals_get_a_board() {
+        assert_eq!((board_width(0, 1, Topology::Square), board_height(0)), (1, 1));
+        assert_eq!((board_width(1, 1, Topology::Square), board_height(1)), (1, 1));
+        assert_eq!((board_width(80, 1, Topology::Square), board_height(24)), (80, 23));
+        assert_eq!((board_width(80, 2, Topology::Square), board_width(1, 2, Topology::Square)), (40, 1));
+        assert_eq!(split_width(1, 1, Topology::Square), 1);
+        assert_eq!(split_width(81, 2, Topology::Square), 20);
+        let mut universe = Universe::new(Board::random(8, 8, 0.5, 3));
+        universe.resize(board_width(1, 1, Topology::Square) as usize, board_height(6) as usize);
+        universe.step();
+        assert_eq!((universe.board.width(), universe.board.height()), (1, 5));
+    }
+
+    #[test]
+    fn rolling_average_window() {
+        let mut average = RollingAverage::default();
+        assert_eq!(average.average(), Duration::ZERO);
+        for ms in 1..=FRAME_TIME_WINDOW as u64 + 2 {
+            average.record(Duration::from_millis(ms));
+        }
+        assert_eq!(average.last(), Duration::from_millis(34));
+        // samples 3..=34
+        assert_eq!(average.average(), Duration::from_micros(18_500));
+    }
+
+    #[test]
+    fn plain_theme_has_no_highlights() {
+        let plain = DEFAULT_THEME.clone().with_fade(3).plain();
+        assert_eq!(plain.born_cell_style, plain.alive_cell_style);
+        assert_eq!(plain.died_cell_style, plain.dead_cell_style);
+        assert!(plain.fade_style(0).is_none());
+    }
+
+    #[test]
+    fn died_cells_fade_out() {
+        let theme = DEFAULT_THEME.clone().with_fade(4);
+        // red to white
+        assert_eq!(theme.fade_style(0).unwrap().foreground_color, Some(Color::Rgb { r: 255, g: 0, b: 0 }));
+        assert_eq!(theme.fade_style(2).unwrap().foreground_color, Some(Color::Rgb { r: 255, g: 128, b: 128 }));
+        assert!(theme.fade_style(4).is_none());
+        assert!(DEFAULT_THEME.clone().with_fade(0).fade_style(0).is_none());
+
+        let board = Board::from_live_coords(5, 5, [(2, 2)]).unwrap();
+        let mut universe = Universe::new(board);
+        assert_eq!(universe.ages[0], u32::MAX);
+        universe.step();
+        assert_eq!(universe.ages[2 * 5 + 2], 0);
+        universe.step();
+        assert_eq!(universe.ages[2 * 5 + 2], 1);
+    }
+
+    #[test]
+    fn invert_resets_highlights() {
+        let board = Board::from_live_coords(4, 4, [(1, 1), (2, 1), (3, 1)]).unwrap();
+        let mut universe = Universe::new(board);
+        universe.step();
+        universe.invert();
+        assert_eq!(universe.board.live_count(), 16 - 3);
+        assert!(universe.board.iter().all(|entry| matches!(entry.cell(), Cell::Alive | Cell::Dead)));
+        assert!(universe.board.iter().all(|entry| {
+            let age = universe.ages[entry.y() * 4 + entry.x()];
+            age == if entry.cell().is_alive() { 0 } else { u32::MAX }
+        }));
+    }
+
+    #[test]
+    fn run_stats() {
+        let board = Board::from_live_coords(5, 5, [(1, 1), (2, 1)]).unwrap();
+        let mut stats = RunStats::new(&board);
+        stats.record(1, 4, true);
+        stats.record(2, 6, true);
+        stats.record(3, 3, false);
+        assert_eq!(stats, RunStats {
+            generations: 3,
+            peak_population: 6,
+            peak_generation: 2,
+            final_population: 3,
+            stabilized: true,
+            seed: None,
+        });
+        assert_eq!(stats.to_string().lines().nth(1), Some("peak population: 6 (generation 2)"));
+        assert!(!stats.to_string().contains("seed"));
+    }
+
+    #[test]
+    fn exit_summary_names_the_seed() {
+        let args = Args::parse(["--seed", "1234", "--manual"].map(String::from)).unwrap();
+        let board = Board::random(10, 10, 0.3, 1234);
+        let state = AppState::new(args, Universe::new(board), THEMES.clone(), (60, 15), 1);
+        assert_eq!(state.stats().to_string().lines().last(), Some("seed: 1234"));
+    }
 }
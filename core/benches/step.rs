@@ -0,0 +1,58 @@
+//! Timings of the hot paths on boards seeded with fixed seeds, so runs are comparable.
+//! `cargo bench -p game_of_life` runs them with Criterion, which reports changes since the last run.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use game_of_life::bit_board::BitBoard;
+use game_of_life::board::Board;
+use game_of_life::logic::{live_neighbours, next_state_with};
+use game_of_life::rle::{from_rle, to_rle};
+use game_of_life::rules::Rules;
+
+const SEED: u64 = 42;
+
+fn next_state(c: &mut Criterion) {
+    let rules = Rules::conway();
+    let mut group = c.benchmark_group("next_state");
+    for size in [32, 128, 512] {
+        for density in [0.1, 0.35] {
+            let soup = Board::random(size, size, density, SEED);
+            let id = BenchmarkId::new(format!("{0}x{0}", size), format!("density {}", density));
+            group.bench_function(id, |b| b.iter(|| {
+                let mut board = soup.clone();
+                next_state_with(&mut board, &rules)
+            }));
+        }
+    }
+    group.finish();
+}
+
+fn bit_board(c: &mut Criterion) {
+    let rules = Rules::conway();
+    let soup = Board::random(512, 512, 0.35, SEED);
+    let mut bits = BitBoard::new(512, 512);
+    for entry in soup.iter().filter(|entry| entry.cell().is_alive()) {
+        bits.set(entry.index(), entry.cell());
+    }
+    c.bench_function("bit_board step 512x512 density 0.35", |b| b.iter(|| {
+        let mut board = bits.clone();
+        board.step_with(&rules)
+    }));
+}
+
+fn neighbours(c: &mut Criterion) {
+    let soup = Board::random(128, 128, 0.35, SEED);
+    c.bench_function("live_neighbours 128x128", |b| b.iter(|| {
+        soup.iter().map(|entry| live_neighbours(black_box(&soup), entry.index()) as u32).sum::<u32>()
+    }));
+}
+
+fn rle(c: &mut Criterion) {
+    let rle = to_rle(&Board::random(256, 256, 0.35, SEED), &Rules::conway());
+    c.bench_function("from_rle 256x256 soup", |b| b.iter(|| from_rle(black_box(&rle)).expect("valid RLE")));
+}
+
+criterion_group!(benches, next_state, bit_board, neighbours, rle);
+criterion_main!(benches);
@@ -0,0 +1,256 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Index;
+
+use crate::board::{Board, Cell};
+use crate::grid::{EdgeBehavior, Grid};
+use crate::logic::next_state_with;
+use crate::rules::Rules;
+
+static ALIVE: Cell = Cell::Alive;
+static DEAD: Cell = Cell::Dead;
+
+/// Board storing one bit of liveness per cell, rows padded to whole `u64` words.
+/// Lifecycle states are not kept: cells read back as either `Alive` or `Dead`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BitBoard {
+    words: Vec<u64>,
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    edges: EdgeBehavior,
+}
+
+impl BitBoard {
+    pub fn new(width: usize, height: usize) -> Self {
+        if width == 0 || height == 0 {
+            panic!("board cannot be zero sized")
+        }
+        let words_per_row = width.div_ceil(64);
+        BitBoard {
+            words: vec![0; words_per_row * height],
+            width,
+            height,
+            words_per_row,
+            edges: EdgeBehavior::Toroidal,
+        }
+    }
+
+    pub fn width(&self) -> usize { self.width }
+    pub fn height(&self) -> usize { self.height }
+    pub fn edges(&self) -> EdgeBehavior { self.edges }
+    pub fn set_edges(&mut self, edges: EdgeBehavior) { self.edges = edges }
+
+    pub fn check_index(&self, (x, y): (usize, usize)) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn is_alive(&self, (x, y): (usize, usize)) -> bool {
+        self.assert_index((x, y));
+        self.words[y * self.words_per_row + x / 64] >> (x % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, (x, y): (usize, usize), cell: Cell) {
+        self.assert_index((x, y));
+        let word = &mut self.words[y * self.words_per_row + x / 64];
+        if cell.is_alive() {
+            *word |= 1 << (x % 64);
+        } else {
+            *word &= !(1 << (x % 64));
+        }
+    }
+
+    pub fn step(&mut self) -> bool {
+        self.step_with(&Rules::conway())
+    }
+
+    /// Advances one generation counting neighbours of 64 cells at a time.
+    pub fn step_with(&mut self, rules: &Rules) -> bool {
+        if self.width < 3 || self.height < 3 || self.edges.is_twisted() {
+            // neighbours wrap onto each other or mirror across the edges here,
+            // leave those to the per-cell path
+            return next_state_with(self, rules);
+        }
+        let mut next = vec![0; self.words.len()];
+        for y in 0..self.height {
+            let mut counts = vec![[0u64; 4]; self.words_per_row];
+            for dy in [-1, 0, 1] {
+                let Some(row) = self.row(y as isize + dy) else {
+                    continue;
+                };
+                add(&mut counts, &self.shifted_right(row));
+                add(&mut counts, &self.shifted_left(row));
+                if dy != 0 {
+                    add(&mut counts, row);
+                }
+            }
+            let alive = &self.words[y * self.words_per_row..(y + 1) * self.words_per_row];
+            for word in 0..self.words_per_row {
+                let mut result = 0;
+                for count in 0..=8u8 {
+                    let birth = if rules.is_birth(count) { !alive[word] } else { 0 };
+                    let survival = if rules.is_survival(count) { alive[word] } else { 0 };
+                    result |= equals(&counts[word], count) & (birth | survival);
+                }
+                next[y * self.words_per_row + word] = result & self.mask(word);
+            }
+        }
+        let changed = next != self.words;
+        self.words = next;
+        changed
+    }
+
+    fn row(&self, y: isize) -> Option<&[u64]> {
+        let y = match self.edges {
+            EdgeBehavior::Bounded if (0..self.height as isize).contains(&y) => y as usize,
+            EdgeBehavior::Bounded => return None,
+            // twisted edges never get here
+            _ => y.rem_euclid(self.height as isize) as usize,
+        };
+        Some(&self.words[y * self.words_per_row..(y + 1) * self.words_per_row])
+    }
+
+    // bit x of the result is bit x - 1 of `row`
+    fn shifted_right(&self, row: &[u64]) -> Vec<u64> {
+        let mut shifted: Vec<u64> = (0..row.len())
+            .map(|word| row[word] << 1 | if word > 0 { row[word - 1] >> 63 } else { 0 })
+            .collect();
+        if self.edges == EdgeBehavior::Toroidal {
+            let last = self.width - 1;
+            shifted[0] |= row[last / 64] >> (last % 64) & 1;
+        }
+        shifted
+    }
+
+    // bit x of the result is bit x + 1 of `row`
+    fn shifted_left(&self, row: &[u64]) -> Vec<u64> {
+        let mut shifted: Vec<u64> = (0..row.len())
+            .map(|word| row[word] >> 1 | row.get(word + 1).map_or(0, |next| next << 63))
+            .collect();
+        if self.edges == EdgeBehavior::Toroidal {
+            let last = self.width - 1;
+            shifted[last / 64] |= (row[0] & 1) << (last % 64);
+        }
+        let last_word = shifted.len() - 1;
+        shifted[last_word] &= self.mask(last_word);
+        shifted
+    }
+
+    fn mask(&self, word: usize) -> u64 {
+        let bits = self.width - word * 64;
+        if bits >= 64 { u64::MAX } else { (1 << bits) - 1 }
+    }
+
+    fn assert_index(&self, (x, y): (usize, usize)) {
+        assert!(x < self.width, "x index {} is out of bound in width {}", x, self.width);
+        assert!(y < self.height, "y index {} is out of bound in height {}", y, self.height);
+    }
+}
+
+// bit sliced counters: bit `i` of the neighbour count of the cell at bit `x`
+// of a word is bit `x` of `counts[word][i]`
+fn add(counts: &mut [[u64; 4]], row: &[u64]) {
+    for (planes, &word) in counts.iter_mut().zip(row) {
+        let mut carry = word;
+        for plane in planes.iter_mut() {
+            let sum = *plane ^ carry;
+            carry &= *plane;
+            *plane = sum;
+        }
+    }
+}
+
+fn equals(planes: &[u64; 4], value: u8) -> u64 {
+    planes.iter().enumerate().fold(u64::MAX, |acc, (bit, plane)| {
+        acc & if value >> bit & 1 == 1 { *plane } else { !*plane }
+    })
+}
+
+impl Grid for BitBoard {
+    fn width(&self) -> usize { self.width }
+    fn height(&self) -> usize { self.height }
+    fn get(&self, index: (usize, usize)) -> Cell { self[index] }
+    fn set(&mut self, index: (usize, usize), cell: Cell) { BitBoard::set(self, index, cell) }
+    fn edges(&self) -> EdgeBehavior { self.edges }
+}
+
+impl Index<(usize, usize)> for BitBoard {
+    type Output = Cell;
+
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        if self.is_alive(index) { &ALIVE } else { &DEAD }
+    }
+}
+
+impl From<&Board> for BitBoard {
+    fn from(board: &Board) -> Self {
+        let mut bits = BitBoard::new(board.width(), board.height());
+        bits.set_edges(board.edges());
+        for entry in board.iter().filter(|entry| entry.cell().is_alive()) {
+            bits.set(entry.index(), Cell::Alive);
+        }
+        bits
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{Board, Cell};
+    use crate::grid::EdgeBehavior;
+    use crate::logic::next_state_with;
+    use crate::rules::Rules;
+
+    use super::BitBoard;
+
+    fn assert_same_liveness(bits: &BitBoard, board: &Board) {
+        for entry in board.iter() {
+            assert_eq!(bits.is_alive(entry.index()), entry.cell().is_alive(), "at {:?}", entry.index());
+        }
+    }
+
+    #[test]
+    fn bit_board_index() {
+        let mut bits = BitBoard::new(130, 2);
+        bits.set((129, 1), Cell::Born);
+        bits.set((64, 0), Cell::Alive);
+        assert_eq!(bits[(129, 1)], Cell::Alive);
+        assert_eq!(bits[(64, 0)], Cell::Alive);
+        assert_eq!(bits[(63, 0)], Cell::Dead);
+        bits.set((64, 0), Cell::Died);
+        assert_eq!(bits[(64, 0)], Cell::Dead);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bit_board_index_not_valid() {
+        let bits = BitBoard::new(3, 3);
+        let _cell = bits[(0, 3)];
+    }
+
+    #[test]
+    fn steps_like_board() {
+        for (width, height) in [(3, 3), (2, 5), (17, 9), (64, 6), (70, 8), (130, 5)] {
+            for edges in [EdgeBehavior::Toroidal, EdgeBehavior::Bounded, EdgeBehavior::Klein, EdgeBehavior::Projective] {
+                for rules in [Rules::conway(), Rules::highlife(), "B0/S8".parse().unwrap()] {
+                    let mut board = Board::random(width, height, 0.4, (width * height) as u64);
+                    board.set_edges(edges);
+                    let mut bits = BitBoard::from(&board);
+                    for _ in 0..6 {
+                        bits.step_with(&rules);
+                        next_state_with(&mut board, &rules);
+                        assert_same_liveness(&bits, &board);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn still_life_does_not_change() {
+        let board = Board::from_live_coords(70, 4, [(65, 1), (66, 1), (65, 2), (66, 2)]).unwrap();
+        let mut bits = BitBoard::from(&board);
+        assert!(!bits.step());
+        assert_same_liveness(&bits, &board);
+    }
+}
@@ -1,12 +1,28 @@
-use std::fmt::{Display, Formatter};
-use std::ops::{Index, IndexMut};
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter, Write};
+use core::ops::{Index, IndexMut};
+use core::str::FromStr;
+
+use crate::grid::{EdgeBehavior, Grid, Topology};
+use crate::logic::{next_state_with, offset_index, valid_neighbour_index, wrap, Generations, NEIGHBOUR_OFFSETS};
+use crate::mask::Mask;
+use crate::pattern::{Pattern, PatternError};
+use crate::rng::Rng;
+use crate::rules::Rules;
+use crate::symmetry::Symmetry;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "char", try_from = "char"))]
+#[repr(u8)]
 pub enum Cell {
-    Died,
-    Dead,
-    Born,
-    Alive,
+    Died = 0,
+    Dead = 1,
+    Born = 2,
+    Alive = 3,
 }
 
 impl Cell {
@@ -19,6 +35,16 @@ impl Cell {
         }
     }
 
+    /// Live cells become dead and the other way around, keeping whether the change is fresh.
+    pub fn inverted(self) -> Cell {
+        match self {
+            Cell::Died => Cell::Born,
+            Cell::Dead => Cell::Alive,
+            Cell::Born => Cell::Died,
+            Cell::Alive => Cell::Dead,
+        }
+    }
+
     pub fn flip(&mut self) {
         if self.is_alive() {
             *self = Cell::Died
@@ -29,7 +55,7 @@ impl Cell {
 }
 
 impl Display for Cell {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             Cell::Dead => write!(f, "X"),
             Cell::Alive => write!(f, "O"),
@@ -50,13 +76,116 @@ impl From<Cell> for char {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+impl TryFrom<char> for Cell {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'x' => Ok(Cell::Died),
+            'X' => Ok(Cell::Dead),
+            'o' => Ok(Cell::Born),
+            'O' => Ok(Cell::Alive),
+            _ => Err(c),
+        }
+    }
+}
+
+impl FromStr for Cell {
+    type Err = BoardError;
+
+    /// Reads a single glyph as written by `Display`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Cell::try_from(c).map_err(BoardError::InvalidCell),
+            _ => Err(BoardError::NotOneCell(s.chars().count())),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BoardError {
+    ZeroSized,
+    OutOfBounds {
+        x: usize,
+        y: usize,
+    },
+    SizeMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    InvalidCell(char),
+    // 1-based, of text that is not in the expected format
+    InvalidLine(usize),
+    RowLength {
+        y: usize,
+        expected: usize,
+        found: usize,
+    },
+    // number of glyphs given where a single cell was expected
+    NotOneCell(usize),
+}
+
+impl Display for BoardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BoardError::ZeroSized => write!(f, "board cannot be zero sized"),
+            BoardError::OutOfBounds { x, y } => write!(f, "cell ({}, {}) is out of the board", x, y),
+            BoardError::SizeMismatch { expected: (w0, h0), found: (w1, h1) } => {
+                write!(f, "expected a {}x{} board, found {}x{}", w0, h0, w1, h1)
+            }
+            BoardError::InvalidCell(c) => write!(f, "invalid cell `{}`, expected `X`, `O`, `x` or `o`", c),
+            BoardError::InvalidLine(number) => write!(f, "invalid line {}", number),
+            BoardError::RowLength { y, expected, found } => {
+                write!(f, "row {} has {} cells instead of {}", y, found, expected)
+            }
+            BoardError::NotOneCell(found) => write!(f, "expected a single cell, found {} glyphs", found),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BoardError {}
+
+/// Population of a board and how it changed with the last step, see `Board::step_stats`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct StepStats {
+    pub population: usize,
+    pub births: usize,
+    pub deaths: usize,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "crate::serialization::BoardRepr", try_from = "crate::serialization::BoardRepr"))]
 pub struct Board {
-    inner: Vec<Cell>,
+    // shared by clones until one of them is changed, which makes snapshots cheap
+    inner: Arc<[Cell]>,
     width: usize,
     height: usize,
+    edges: EdgeBehavior,
+    seam: (usize, usize),
+    topology: Topology,
+    // cells outside are no part of the board, e.g. the corners of a circular arena
+    shape: Option<Mask>,
+    // every cell is known to be `Dead`, so stepping can skip the scan
+    settled: bool,
+}
+
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.edges == other.edges
+            && self.seam == other.seam
+            && self.topology == other.topology
+            && self.shape == other.shape
+            && (Arc::ptr_eq(&self.inner, &other.inner) || self.inner == other.inner)
+    }
 }
 
+impl Eq for Board {}
+
 impl Board {
     pub fn check_index(&self, (x, y): (usize, usize)) -> bool {
         x < self.width() && y < self.height()
@@ -69,33 +198,456 @@ impl Board {
             panic!("board cannot be zero sized")
         }
         Board {
-            inner: vec![Cell::Dead; width * height],
+            inner: vec![Cell::Dead; width * height].into(),
             width,
             height,
+            edges: EdgeBehavior::Toroidal,
+            seam: (0, 0),
+            topology: Topology::Square,
+            shape: None,
+            settled: true,
+        }
+    }
+
+    /// Board with exactly the given cells alive, coordinates outside of it are an error.
+    pub fn from_live_coords(
+        width: usize,
+        height: usize,
+        coords: impl IntoIterator<Item = (usize, usize)>,
+    ) -> Result<Self, BoardError> {
+        if width == 0 || height == 0 {
+            return Err(BoardError::ZeroSized);
+        }
+        let mut board = Board::new(width, height);
+        for (x, y) in coords {
+            if !board.check_index((x, y)) {
+                return Err(BoardError::OutOfBounds { x, y });
+            }
+            board[(x, y)] = Cell::Alive;
+        }
+        Ok(board)
+    }
+
+    /// Board spanning the bounding box of a Life 1.06 pattern, shifted so that the smallest
+    /// coordinates land on `(0, 0)`.
+    pub fn from_life106(s: &str) -> Result<Self, PatternError> {
+        let mut coords = Vec::new();
+        for (number, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace().map(str::parse::<isize>);
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(Ok(x)), Some(Ok(y)), None) => coords.push((x, y)),
+                _ => return Err(PatternError::Line(number + 1)),
+            }
+        }
+        let min_x = coords.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = coords.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        Ok(coords.into_iter()
+            .map(|(x, y)| (x.abs_diff(min_x), y.abs_diff(min_y)))
+            .collect())
+    }
+
+    /// The size of the board on the first line, `WIDTH HEIGHT`, then `x y` of every live cell
+    /// row by row. Read back by `load_coords`.
+    pub fn save_coords(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{} {}", self.width, self.height);
+        for (y, row) in self.rows().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if cell.is_alive() {
+                    let _ = writeln!(out, "{} {}", x, y);
+                }
+            }
+        }
+        out
+    }
+
+    /// Board written by `save_coords`, blank lines are skipped.
+    pub fn load_coords(s: &str) -> Result<Self, BoardError> {
+        let mut lines = s.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| {
+                let mut parts = line.split_whitespace().map(str::parse::<usize>);
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(Ok(a)), Some(Ok(b)), None) => Ok((a, b)),
+                    _ => Err(BoardError::InvalidLine(number + 1)),
+                }
+            });
+        let (width, height) = lines.next().unwrap_or(Err(BoardError::ZeroSized))?;
+        Board::from_live_coords(width, height, lines.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Random soup where each cell is alive with probability `density`.
+    pub fn random(width: usize, height: usize, density: f64, seed: u64) -> Self {
+        let mut board = Board::new(width, height);
+        let mut rng = Rng::new(seed);
+        board.settled = false;
+        for cell in board.cells_mut() {
+            if rng.next_f64() < density {
+                *cell = Cell::Alive;
+            }
         }
+        board
+    }
+
+    /// Random soup mirrored across the axes of `symmetry`, only its fundamental region is
+    /// drawn at random. Without symmetry it is the same soup as `random` gives.
+    pub fn random_symmetric(width: usize, height: usize, density: f64, seed: u64, symmetry: Symmetry) -> Self {
+        let mut board = Board::new(width, height);
+        let mut rng = Rng::new(seed);
+        board.settled = false;
+        let (region_width, region_height) = symmetry.fundamental(width, height);
+        for y in 0..region_height {
+            for x in 0..region_width {
+                if rng.next_f64() < density {
+                    for index in symmetry.mirrored((x, y), width, height) {
+                        board[index] = Cell::Alive;
+                    }
+                }
+            }
+        }
+        board
     }
 
     pub fn width(&self) -> usize { self.width }
     pub fn height(&self) -> usize { self.height }
-    pub fn iter(&self) -> BoardIter { self.into_iter() }
+    pub fn iter(&self) -> BoardIter<'_> { self.into_iter() }
+
+    /// Top left and bottom right corners of the smallest rectangle holding every live cell.
+    pub fn live_bounds(&self) -> Option<((usize, usize), (usize, usize))> {
+        let mut live = self.iter().filter(|entry| entry.cell().is_alive()).map(|entry| entry.index());
+        let first = live.next()?;
+        Some(live.fold((first, first), |((x0, y0), (x1, y1)), (x, y)| {
+            ((x0.min(x), y0.min(y)), (x1.max(x), y1.max(y)))
+        }))
+    }
+
+    /// Rows from top to bottom, each a slice of `width` cells.
+    pub fn rows(&self) -> impl Iterator<Item = &[Cell]> + '_ {
+        self.inner.chunks(self.width)
+    }
+
+    /// Columns from left to right, each yielding its `height` cells from the top.
+    pub fn cols(&self) -> impl Iterator<Item = impl Iterator<Item = Cell> + '_> + '_ {
+        (0..self.width).map(move |x| self.inner.iter().skip(x).step_by(self.width).copied())
+    }
+    pub fn center_of(&self) -> (usize, usize) { (self.width / 2, self.height / 2) }
+    pub fn live_count(&self) -> usize { self.inner.iter().filter(|cell| cell.is_alive()).count() }
+
+    /// Live cells within the `size` wide rectangle at `top_left`, clipped to the board.
+    pub fn population_in(&self, (x, y): (usize, usize), (width, height): (usize, usize)) -> usize {
+        let (right, bottom) = (x.saturating_add(width).min(self.width), y.saturating_add(height).min(self.height));
+        if x >= right {
+            return 0;
+        }
+        self.rows()
+            .take(bottom)
+            .skip(y)
+            .map(|row| row[x..right].iter().filter(|cell| cell.is_alive()).count())
+            .sum()
+    }
+
+    /// Live cells along with the births and deaths of the last step.
+    pub fn step_stats(&self) -> StepStats {
+        let mut stats = StepStats::default();
+        for cell in self.inner.iter() {
+            match cell {
+                Cell::Born => stats.births += 1,
+                Cell::Died => stats.deaths += 1,
+                _ => {}
+            }
+            stats.population += cell.is_alive() as usize;
+        }
+        stats
+    }
+
+    pub fn edges(&self) -> EdgeBehavior { self.edges }
+    pub fn set_edges(&mut self, edges: EdgeBehavior) { self.edges = edges }
+    pub fn seam(&self) -> (usize, usize) { self.seam }
+    pub fn set_seam(&mut self, (x, y): (usize, usize)) { self.seam = (x % self.width, y % self.height) }
+    pub fn topology(&self) -> Topology { self.topology }
+    pub fn set_topology(&mut self, topology: Topology) { self.topology = topology }
+    pub fn shape(&self) -> Option<&Mask> { self.shape.as_ref() }
+
+    /// Restricts the board to the cells inside `shape`, the ones outside are killed, stay dead
+    /// and are left out of neighbour counts. `None` makes the whole rectangle the board again.
+    pub fn set_shape(&mut self, shape: Option<Mask>) {
+        if let Some(shape) = &shape {
+            assert_eq!((shape.width(), shape.height()), (self.width, self.height), "shape of another board size");
+            let width = self.width;
+            for (index, cell) in self.cells_mut().iter_mut().enumerate() {
+                if !shape.contains((index % width, index / width)) {
+                    *cell = Cell::Dead;
+                }
+            }
+        }
+        self.shape = shape;
+    }
+
+    pub fn in_shape(&self, index: (usize, usize)) -> bool {
+        self.shape.as_ref().is_none_or(|shape| shape.contains(index))
+    }
 
     pub fn set(&mut self, (x, y): (usize, usize), cell: Cell) {
         assert!(x < self.width, "x index {} is out of bound in width {}", x, self.width);
         assert!(y < self.height, "y index {} is out of bound in height {}", y, self.height);
-        self.inner[y * self.width + x] = cell
+        self.settled &= cell == Cell::Dead;
+        // unchanged cells leave the storage shared with clones
+        let index = y * self.width + x;
+        if self.inner[index] != cell {
+            self.cells_mut()[index] = cell
+        }
+    }
+
+    /// Cell in column `x` and row `y`, the same as `board[(x, y)]`.
+    pub fn at(&self, x: usize, y: usize) -> Cell { self[(x, y)] }
+
+    /// Cell in `row` and `col`, for code thinking in matrix order: `at_rc(r, c) == at(c, r)`.
+    pub fn at_rc(&self, row: usize, col: usize) -> Cell { self[(col, row)] }
+
+    pub fn fill(&mut self, cell: Cell) {
+        self.cells_mut().fill(cell);
+        self.settled = cell == Cell::Dead;
+    }
+
+    /// Replaces every cell with `f(cell, (x, y))`.
+    pub fn map_in_place(&mut self, f: impl Fn(Cell, (usize, usize)) -> Cell) {
+        let width = self.width;
+        for (index, cell) in self.cells_mut().iter_mut().enumerate() {
+            *cell = f(*cell, (index % width, index / width));
+        }
+        self.settled = false;
+    }
+
+    /// Cells whose state differs in `other` as `(x, y, old, new)` in row-major order, including
+    /// cells that only settled, such as `Died` turning `Dead`.
+    pub fn diff(&self, other: &Board) -> Result<Vec<(usize, usize, Cell, Cell)>, BoardError> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(BoardError::SizeMismatch {
+                expected: (self.width, self.height),
+                found: (other.width, other.height),
+            });
+        }
+        let changes = self.inner.iter()
+            .zip(other.inner.iter())
+            .enumerate()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(index, (&old, &new))| (index % self.width, index / self.width, old, new))
+            .collect();
+        Ok(changes)
+    }
+
+    /// Rows of `O` for live and `.` for dead cells, each ending in a newline.
+    pub fn write_ascii(&self, out: &mut impl core::fmt::Write) -> core::fmt::Result {
+        write!(out, "{}", self.display_with('O', '.', false))
+    }
+
+    /// Displays live cells as `alive` and dead ones as `dead`, under a `Board: ` line with `prefix`.
+    pub fn display_with(&self, alive: char, dead: char, prefix: bool) -> BoardDisplay<'_> {
+        BoardDisplay { board: self, alive, dead, prefix }
+    }
+
+    /// Advances one generation under Conway's rules, returns whether anything changed.
+    pub fn step(&mut self) -> bool {
+        self.step_with(&Rules::conway())
+    }
+
+    pub fn step_with(&mut self, rules: &Rules) -> bool {
+        next_state_with(self, rules)
+    }
+
+    /// Lazily yields the following generations, starting with the next one. Every
+    /// item is a fresh clone of the board on top of the snapshot a step takes anyway,
+    /// so prefer stepping in place when the intermediate boards are not needed.
+    pub fn generations(self, rules: Rules) -> Generations {
+        Generations::new(self, rules)
+    }
+
+    /// Makes the cells of `pattern` alive with its top left corner at `(x, y)`,
+    /// cells falling outside the board are clipped.
+    pub fn stamp(&mut self, pattern: &Pattern, (x, y): (usize, usize)) {
+        for &(px, py) in pattern.cells() {
+            let index = (x + px, y + py);
+            if self.check_index(index) {
+                self[index] = Cell::Alive;
+            }
+        }
+    }
+
+    /// Coordinates of the 8 Moore neighbours of `(x, y)` row by row, wrapped around the edges as
+    /// on a plain torus. The board's edge behaviour, seam, shape and topology are ignored, unlike
+    /// by `neighbor_bitmask` and stepping. Boards narrower than 3 cells list some neighbours more
+    /// than once.
+    pub fn neighbor_coords(&self, (x, y): (usize, usize)) -> [(usize, usize); 8] {
+        NEIGHBOUR_OFFSETS.map(|(dx, dy)| (wrap(x, dx, self.width), wrap(y, dy, self.height)))
+    }
+
+    /// Which of the 8 Moore neighbours of `(x, y)` are alive, one bit each clockwise from the
+    /// north: bit 0 is N, then NE, E, SE, S, SW, W and bit 7 NW. Neighbours beyond a bounded
+    /// edge or outside the shape count as dead, the others follow the edges like stepping does.
+    /// Hexagonal boards are read as square ones.
+    pub fn neighbor_bitmask(&self, index: (usize, usize)) -> u8 {
+        const CLOCKWISE: [(isize, isize); 8] = [(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)];
+        CLOCKWISE.iter().enumerate().fold(0, |mask, (bit, &(dx, dy))| {
+            match valid_neighbour_index(self, index, dx, dy) {
+                Some(neighbour) if self.in_shape(neighbour) && self[neighbour].is_alive() => mask | 1 << bit,
+                _ => mask,
+            }
+        })
+    }
+
+    /// Top left corners and orientations at which `needle` occurs, with exactly its live cells in
+    /// its bounding box. Orientations 0 to 3 are the needle turned that many quarter turns clockwise,
+    /// 4 to 7 the needle mirrored left to right and then turned by 4 less. Matches may cross the
+    /// edges and seam wherever stepping does, mirrored like its neighbours on Klein bottles and
+    /// projective planes. A needle without live cells is found nowhere.
+    pub fn find_patterns(&self, needle: &Pattern) -> Vec<((usize, usize), u8)> {
+        if needle.cells().is_empty() {
+            return Vec::new();
+        }
+        let mut orientations = Vec::new();
+        let mut seen: Vec<Vec<(usize, usize)>> = Vec::new();
+        for start in [needle.clone(), needle.mirrored()] {
+            let mut rotated = start;
+            for _ in 0..4 {
+                let mut cells = rotated.cells().to_vec();
+                cells.sort_unstable();
+                // symmetric needles look the same in several orientations, report each match once
+                orientations.push((!seen.contains(&cells)).then(|| rotated.clone()));
+                seen.push(cells);
+                rotated = rotated.rotated();
+            }
+        }
+        let mut found = Vec::new();
+        for oy in 0..self.height {
+            for ox in 0..self.width {
+                for (orientation, pattern) in orientations.iter().enumerate() {
+                    let Some(pattern) = pattern else { continue };
+                    let (width, height) = (pattern.width(), pattern.height());
+                    if width > self.width || height > self.height {
+                        continue;
+                    }
+                    let matches = (0..height).all(|y| (0..width).all(|x| {
+                        offset_index(self, (ox, oy), x as isize, y as isize)
+                            .is_some_and(|index| self[index].is_alive() == pattern.cells().contains(&(x, y)))
+                    }));
+                    if matches {
+                        found.push(((ox, oy), orientation as u8));
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    // copies the cells first if a clone still shares them
+    fn cells_mut(&mut self) -> &mut [Cell] {
+        Arc::make_mut(&mut self.inner)
+    }
+
+    /// Cells in row-major order, the cell at `(x, y)` is `y * width + x`.
+    pub fn as_slice(&self) -> &[Cell] {
+        &self.inner
+    }
+
+    /// Cells in row-major order like `as_slice`, to be changed in place. They are copied
+    /// first if a clone of the board still shares them.
+    pub fn as_mut_slice(&mut self) -> &mut [Cell] {
+        self.settled = false;
+        self.cells_mut()
+    }
+
+    /// Cells as their `u8` discriminants in row-major order, e.g. for handing over to JS.
+    pub fn cells_u8(&self) -> &[u8] {
+        // SAFETY: `Cell` is a fieldless `#[repr(u8)]` enum, so it has the size,
+        // alignment and bit validity of a `u8`
+        unsafe { core::slice::from_raw_parts(self.inner.as_ptr().cast::<u8>(), self.inner.len()) }
+    }
+}
+
+impl Grid for Board {
+    fn width(&self) -> usize { self.width }
+    fn height(&self) -> usize { self.height }
+    fn get(&self, index: (usize, usize)) -> Cell { self[index] }
+    fn set(&mut self, index: (usize, usize), cell: Cell) { Board::set(self, index, cell) }
+    fn edges(&self) -> EdgeBehavior { self.edges }
+    fn seam(&self) -> (usize, usize) { self.seam }
+    fn topology(&self) -> Topology { self.topology }
+    fn in_shape(&self, index: (usize, usize)) -> bool { Board::in_shape(self, index) }
+    fn is_settled(&self) -> bool { self.settled }
+    fn set_settled(&mut self, settled: bool) { self.settled = settled }
+}
+
+/// Collects live cells into the smallest board containing all of them.
+impl FromIterator<(usize, usize)> for Board {
+    fn from_iter<T: IntoIterator<Item = (usize, usize)>>(iter: T) -> Self {
+        let pattern = Pattern::from_cells(iter);
+        let mut board = Board::new(pattern.width().max(1), pattern.height().max(1));
+        board.stamp(&pattern, (0, 0));
+        board
     }
 }
 
 impl Display for Board {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let mut str = String::new();
-        for row in 0..self.height() {
-            for col in 0..self.width() {
-                str.push(self[(col, row)].into());
-            }
+        for row in self.rows() {
+            str.extend(row.iter().map(|&cell| char::from(cell)));
             str.push('\n');
         }
-        write!(f, "Board: \n{}", str)
+        f.write_str(&str)
+    }
+}
+
+impl FromStr for Board {
+    type Err = BoardError;
+
+    /// Reads the grid written by `Display`, one line per row, every row as wide as the first.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<_> = s.lines().collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        if width == 0 {
+            return Err(BoardError::ZeroSized);
+        }
+        let mut board = Board::new(width, rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            let found = row.chars().count();
+            if found != width {
+                return Err(BoardError::RowLength { y, expected: width, found });
+            }
+            for (x, c) in row.chars().enumerate() {
+                board[(x, y)] = Cell::try_from(c).map_err(BoardError::InvalidCell)?;
+            }
+        }
+        Ok(board)
+    }
+}
+
+/// A board with chosen glyphs for live and dead cells, see `Board::display_with`.
+#[derive(Copy, Clone, Debug)]
+pub struct BoardDisplay<'a> {
+    board: &'a Board,
+    alive: char,
+    dead: char,
+    prefix: bool,
+}
+
+impl Display for BoardDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if self.prefix {
+            f.write_str("Board: \n")?;
+        }
+        for row in self.board.rows() {
+            for cell in row {
+                f.write_char(if cell.is_alive() { self.alive } else { self.dead })?;
+            }
+            f.write_char('\n')?;
+        }
+        Ok(())
     }
 }
 
@@ -113,7 +665,9 @@ impl IndexMut<(usize, usize)> for Board {
     fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
         assert!(x < self.width, "x index {} is out of bound in width {}", x, self.width);
         assert!(y < self.height, "y index {} is out of bound in height {}", y, self.height);
-        &mut self.inner[y * self.width + x]
+        self.settled = false;
+        let width = self.width;
+        &mut self.cells_mut()[y * width + x]
     }
 }
 
@@ -195,7 +749,17 @@ impl<'a> IntoIterator for &'a Board {
 
 #[cfg(test)]
 mod tests {
-    use super::{Board, Cell};
+    use crate::grid::Grid;
+    use crate::logic::{live_neighbours, next_state, next_state_with};
+    use crate::rules::Rules;
+
+    use crate::grid::EdgeBehavior;
+    use crate::pattern::{Pattern, PatternError};
+    use crate::symmetry::Symmetry;
+
+    use alloc::sync::Arc;
+
+    use super::{Board, BoardError, Cell, StepStats};
 
     #[test]
     fn create_board() {
@@ -245,4 +809,357 @@ mod tests {
         cell.flip();
         assert_eq!(cell, Cell::Born);
     }
+
+    #[test]
+    fn diff_of_stepping_blinker() {
+        let mut board = Board::from_live_coords(5, 5, [(1, 2), (2, 2), (3, 2)]).unwrap();
+        let before = board.clone();
+        board.step();
+        assert_eq!(before.diff(&board), Ok(vec![
+            (2, 1, Cell::Dead, Cell::Born),
+            (1, 2, Cell::Alive, Cell::Died),
+            (3, 2, Cell::Alive, Cell::Died),
+            (2, 3, Cell::Dead, Cell::Born),
+        ]));
+        assert_eq!(board.diff(&board), Ok(vec![]));
+        assert_eq!(
+            board.diff(&Board::new(5, 4)),
+            Err(BoardError::SizeMismatch { expected: (5, 5), found: (5, 4) }),
+        );
+    }
+
+    #[test]
+    fn row_and_column_accessors() {
+        // 4 columns, 2 rows
+        let board = Board::from_live_coords(4, 2, [(3, 1)]).unwrap();
+        assert_eq!(board.at(3, 1), Cell::Alive);
+        assert_eq!(board.at_rc(1, 3), Cell::Alive);
+        assert_eq!(board.at_rc(1, 3), board[(3, 1)]);
+        assert!(!board.at_rc(0, 1).is_alive());
+    }
+
+    #[test]
+    fn step_in_place() {
+        let mut board = Board::from_live_coords(5, 5, [(1, 2), (2, 2), (3, 2)]).unwrap();
+        let mut expected = board.clone();
+        next_state(&mut expected);
+        assert!(board.step());
+        assert_eq!(board, expected);
+        assert!(board.step_with(&Rules::highlife()));
+        assert_eq!(board.live_count(), 3);
+        assert!(!Board::new(4, 4).step());
+    }
+
+    #[test]
+    fn fill_and_map() {
+        let original = Board::random(7, 5, 0.5, 11);
+        let mut board = original.clone();
+        board.map_in_place(|cell, _| cell.inverted());
+        assert_eq!(board.live_count(), 35 - original.live_count());
+        board.map_in_place(|cell, _| cell.inverted());
+        assert_eq!(board, original);
+
+        board.map_in_place(|cell, (x, _)| if x == 0 { Cell::Alive } else { cell });
+        assert!((0..5).all(|y| board[(0, y)] == Cell::Alive));
+        board.fill(Cell::Dead);
+        assert_eq!(board, Board::new(7, 5));
+        assert!(!next_state(&mut board));
+    }
+
+    #[test]
+    fn neighbor_coords() {
+        let board = Board::new(4, 3);
+        assert_eq!(board.neighbor_coords((1, 1)), [(0, 0), (1, 0), (2, 0), (0, 1), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        assert_eq!(board.neighbor_coords((0, 0)), [(3, 2), (0, 2), (1, 2), (3, 0), (1, 0), (3, 1), (0, 1), (1, 1)]);
+        assert_eq!(board.neighbor_coords((3, 0)), [(2, 2), (3, 2), (0, 2), (2, 0), (0, 0), (2, 1), (3, 1), (0, 1)]);
+        assert_eq!(board.neighbor_coords((0, 2)), [(3, 1), (0, 1), (1, 1), (3, 2), (1, 2), (3, 0), (0, 0), (1, 0)]);
+        assert_eq!(board.neighbor_coords((3, 2)), [(2, 1), (3, 1), (0, 1), (2, 2), (0, 2), (2, 0), (3, 0), (0, 0)]);
+    }
+
+    #[test]
+    fn find_gliders() {
+        let mut board = Board::new(12, 10);
+        board.stamp(&Pattern::glider(), (1, 1));
+        board.stamp(&Pattern::glider().rotated().rotated(), (7, 2));
+        // wraps around the bottom right corner
+        for (x, y) in Pattern::glider().rotated().cells() {
+            board[((x + 10) % 12, (y + 9) % 10)] = Cell::Alive;
+        }
+        let mut found = board.find_patterns(&Pattern::glider());
+        found.sort_unstable();
+        assert_eq!(found, [((1, 1), 0), ((7, 2), 2), ((10, 9), 1)]);
+        board.set_edges(EdgeBehavior::Bounded);
+        assert_eq!(board.find_patterns(&Pattern::glider()).len(), 2);
+
+        let block = Pattern::from_cells([(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert_eq!(board.find_patterns(&block), []);
+        board.stamp(&block, (4, 6));
+        assert_eq!(board.find_patterns(&block), [((4, 6), 0)]);
+        assert_eq!(board.find_patterns(&Pattern::from_cells([])), []);
+
+        // reflections count, matches across the seam of a bounded board do not
+        let mut board = Board::new(8, 8);
+        board.stamp(&Pattern::glider().mirrored(), (2, 2));
+        assert_eq!(board.find_patterns(&Pattern::glider()), [((2, 2), 4)]);
+        board.set_edges(EdgeBehavior::Bounded);
+        board.set_seam((3, 0));
+        assert_eq!(board.find_patterns(&Pattern::glider()), []);
+
+        // a glider crossing the twisted edge of a Klein bottle comes back mirrored, and is still found
+        let mut board = Board::new(10, 10);
+        board.set_edges(EdgeBehavior::Klein);
+        board.stamp(&Pattern::glider(), (5, 1));
+        let phases = [Pattern::glider(), Pattern::from_plaintext("O.O\n.OO\n.O.").unwrap()];
+        for _ in 0..40 {
+            board.step();
+            assert_eq!(phases.iter().map(|phase| board.find_patterns(phase).len()).sum::<usize>(), 1);
+        }
+    }
+
+    #[test]
+    fn from_life106() {
+        let board = Board::from_life106("#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n").unwrap();
+        assert_eq!((board.width(), board.height()), (3, 3));
+        assert_eq!(board.to_string(), "XOX\nXXO\nOOO\n");
+        assert_eq!(Board::from_life106("#Life 1.06\n").unwrap().live_count(), 0);
+        assert_eq!(Board::from_life106("#Life 1.06\n1 2\n3\n"), Err(PatternError::Line(3)));
+    }
+
+    #[test]
+    fn cells_u8_is_row_major() {
+        let mut board = Board::new(3, 2);
+        board[(1, 0)] = Cell::Alive;
+        board[(2, 1)] = Cell::Born;
+        assert_eq!(board.cells_u8(), &[1, 3, 1, 1, 1, 2]);
+    }
+
+    #[test]
+    fn raw_cell_slices() {
+        let mut board = Board::new(4, 3);
+        let snapshot = board.clone();
+        for x in 1..4 {
+            board.as_mut_slice()[4 + x] = Cell::Alive;
+        }
+        assert_eq!(board[(2, 1)], Cell::Alive);
+        assert_eq!(board.as_slice()[2 * 4 + 2], board[(2, 2)]);
+        assert_eq!(snapshot.live_count(), 0);
+        // the blinker is stepped although the board was empty, so settled, before
+        next_state(&mut board);
+        assert_eq!(board.live_count(), 3);
+        assert!(board[(2, 0)].is_alive() && board[(2, 2)].is_alive());
+    }
+
+    #[test]
+    fn center() {
+        assert_eq!(Board::new(5, 8).center_of(), (2, 4));
+        assert_eq!(Board::new(1, 1).center_of(), (0, 0));
+    }
+
+    #[test]
+    fn random_is_reproducible() {
+        assert_eq!(Board::random(10, 10, 0.5, 3), Board::random(10, 10, 0.5, 3));
+        assert_ne!(Board::random(10, 10, 0.5, 3), Board::random(10, 10, 0.5, 4));
+        assert!(Board::random(4, 4, 0.0, 1).iter().all(|entry| entry.cell() == Cell::Dead));
+        assert!(Board::random(4, 4, 1.0, 1).iter().all(|entry| entry.cell() == Cell::Alive));
+    }
+
+    #[test]
+    fn symmetric_soups() {
+        assert_eq!(Board::random_symmetric(9, 7, 0.4, 3, Symmetry::None), Board::random(9, 7, 0.4, 3));
+        for (width, height) in [(9, 7), (8, 6)] {
+            let board = Board::random_symmetric(width, height, 0.4, 3, Symmetry::FourFold);
+            assert!(board.live_count() > 0);
+            for entry in board.iter() {
+                for index in Symmetry::FourFold.mirrored(entry.index(), width, height) {
+                    assert_eq!(board[index], entry.cell());
+                }
+            }
+        }
+        let vertical = Board::random_symmetric(5, 4, 0.5, 8, Symmetry::Vertical);
+        assert!(vertical.rows().all(|row| row.iter().eq(row.iter().rev())));
+    }
+
+    #[test]
+    fn from_live_coords() {
+        let board = Board::from_live_coords(3, 2, [(0, 0), (2, 1)]).unwrap();
+        assert_eq!(board.to_string(), "OXX\nXXO\n");
+        assert_eq!(Board::from_live_coords(3, 2, [(3, 0)]), Err(BoardError::OutOfBounds { x: 3, y: 0 }));
+        assert_eq!(Board::from_live_coords(0, 2, []), Err(BoardError::ZeroSized));
+    }
+
+    #[test]
+    fn collect_coords() {
+        let board: Board = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)].into_iter().collect();
+        assert_eq!((board.width(), board.height()), (3, 3));
+        assert_eq!(board.iter().filter(|entry| entry.cell().is_alive()).count(), 5);
+        let empty: Board = core::iter::empty().collect();
+        assert_eq!((empty.width(), empty.height()), (1, 1));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut board = Board::from_live_coords(4, 3, [(0, 0), (1, 0), (3, 2)]).unwrap();
+        board[(2, 1)] = Cell::Died;
+        board.set_edges(crate::grid::EdgeBehavior::Bounded);
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!(json, r#"{"width":4,"height":3,"edges":"Bounded","cells":"2O4Xx4XO"}"#);
+        assert_eq!(serde_json::from_str::<Board>(&json).unwrap(), board);
+        board.set_topology(crate::grid::Topology::Hex);
+        let json = serde_json::to_string(&board).unwrap();
+        assert!(json.contains(r#""topology":"Hex""#));
+        assert_eq!(serde_json::from_str::<Board>(&json).unwrap(), board);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_rejects_wrong_cell_count() {
+        let json = r#"{"width":2,"height":2,"cells":"3X"}"#;
+        assert!(serde_json::from_str::<Board>(json).is_err());
+        let json = r#"{"width":2,"height":2,"cells":"5X"}"#;
+        assert!(serde_json::from_str::<Board>(json).is_err());
+        let json = r#"{"width":2,"height":2,"cells":"3Xo"}"#;
+        assert_eq!(serde_json::from_str::<Board>(json).unwrap()[(1, 1)], Cell::Born);
+    }
+
+    #[test]
+    fn generations() {
+        let blinker = Board::from_live_coords(5, 5, [(1, 2), (2, 2), (3, 2)]).unwrap();
+        let mut generations = blinker.clone().generations(Rules::conway());
+        assert!(generations.next().unwrap()[(2, 1)].is_alive());
+        assert!(generations.next().unwrap()[(1, 2)].is_alive());
+        let last = blinker.generations(Rules::conway()).take(10).last().unwrap();
+        assert!(last[(3, 2)].is_alive() && !last[(2, 1)].is_alive());
+    }
+
+    #[test]
+    fn clones_share_cells_until_changed() {
+        let block = Board::from_live_coords(4, 4, [(1, 1), (2, 1), (1, 2), (2, 2)]).unwrap();
+        let mut stepped = block.clone();
+        assert!(Arc::ptr_eq(&block.inner, &stepped.inner));
+        // a still life writes nothing, so nothing is copied
+        stepped.step();
+        assert!(Arc::ptr_eq(&block.inner, &stepped.inner));
+        stepped[(0, 0)] = Cell::Alive;
+        assert!(!Arc::ptr_eq(&block.inner, &stepped.inner));
+        assert_eq!(block[(0, 0)], Cell::Dead);
+    }
+
+    #[test]
+    fn live_bounds() {
+        assert_eq!(Board::new(4, 4).live_bounds(), None);
+        let mut board = Board::from_live_coords(8, 6, [(5, 1), (2, 4), (3, 2)]).unwrap();
+        assert_eq!(board.live_bounds(), Some(((2, 1), (5, 4))));
+        board[(2, 4)] = Cell::Died;
+        assert_eq!(board.live_bounds(), Some(((3, 1), (5, 2))));
+    }
+
+    #[test]
+    fn rows_and_cols() {
+        let board = Board::from_live_coords(4, 3, [(1, 0), (3, 2)]).unwrap();
+        assert_eq!(board.rows().count(), board.height());
+        assert!(board.rows().all(|row| row.len() == board.width()));
+        assert_eq!(board.rows().nth(2).unwrap()[3], Cell::Alive);
+        assert_eq!(board.cols().count(), board.width());
+        let column: Vec<_> = board.cols().nth(1).unwrap().collect();
+        assert_eq!(column, [Cell::Alive, Cell::Dead, Cell::Dead]);
+    }
+
+    #[test]
+    fn write_ascii() {
+        let mut board = Board::from_live_coords(3, 2, [(0, 0), (2, 1)]).unwrap();
+        board[(1, 0)] = Cell::Died;
+        let mut ascii = String::new();
+        board.write_ascii(&mut ascii).unwrap();
+        assert_eq!(ascii, "O..\n..O\n");
+        assert_eq!(board.to_string(), "OxX\nXXO\n");
+        assert_eq!(board.display_with('#', ' ', true).to_string(), "Board: \n#  \n  #\n");
+    }
+
+    #[test]
+    fn parse_display() {
+        let board: Board = "OxX\nXoO\n".parse().unwrap();
+        assert_eq!((board.width(), board.height()), (3, 2));
+        assert_eq!((board[(1, 0)], board[(1, 1)]), (Cell::Died, Cell::Born));
+        assert_eq!(board.to_string().parse(), Ok(board));
+        for cell in [Cell::Died, Cell::Dead, Cell::Born, Cell::Alive] {
+            assert_eq!(cell.to_string().parse(), Ok(cell));
+        }
+        assert_eq!("OO".parse::<Cell>(), Err(BoardError::NotOneCell(2)));
+        assert_eq!("".parse::<Cell>(), Err(BoardError::NotOneCell(0)));
+        assert_eq!("OX\nO\n".parse::<Board>(), Err(BoardError::RowLength { y: 1, expected: 2, found: 1 }));
+        assert_eq!("O.".parse::<Board>(), Err(BoardError::InvalidCell('.')));
+        assert_eq!("".parse::<Board>(), Err(BoardError::ZeroSized));
+    }
+
+    #[test]
+    fn coords_round_trip() {
+        let board = Board::from_live_coords(7, 3, [(6, 2), (0, 0), (3, 1)]).unwrap();
+        assert_eq!(board.save_coords(), "7 3\n0 0\n3 1\n6 2\n");
+        assert_eq!(Board::load_coords(&board.save_coords()), Ok(board));
+        let empty = Board::new(40, 1);
+        assert_eq!(Board::load_coords(&empty.save_coords()), Ok(empty));
+        assert_eq!(Board::load_coords("2 2\n\n1 1\n"), Board::from_live_coords(2, 2, [(1, 1)]));
+        assert_eq!(Board::load_coords("2 2\n2 0\n"), Err(BoardError::OutOfBounds { x: 2, y: 0 }));
+        assert_eq!(Board::load_coords("2 2\n1 -1\n"), Err(BoardError::InvalidLine(2)));
+        assert_eq!(Board::load_coords("2\n"), Err(BoardError::InvalidLine(1)));
+        assert_eq!(Board::load_coords(""), Err(BoardError::ZeroSized));
+    }
+
+    #[test]
+    fn empty_board_settles() {
+        let mut board = Board::new(4, 4);
+        assert!(board.is_settled());
+        assert!(!next_state(&mut board));
+
+        board[(1, 1)] = Cell::Alive;
+        assert!(!board.is_settled());
+        assert_eq!(board.live_count(), 1);
+        assert!(next_state(&mut board));
+        assert_eq!(board[(1, 1)], Cell::Died);
+        assert!(!board.is_settled());
+        assert!(next_state(&mut board));
+        assert!(board.is_settled());
+        assert_eq!(board, Board::new(4, 4));
+
+        assert!(next_state_with(&mut board, &"B0/S".parse().unwrap()));
+        assert_eq!(board.live_count(), 16);
+    }
+
+    #[test]
+    fn neighbor_bitmask() {
+        let mut board = Board::new(6, 6);
+        board.stamp(&Pattern::glider(), (1, 1));
+        // N, E, SE, S and SW of the middle of the glider
+        assert_eq!(board.neighbor_bitmask((2, 2)), 0b0011_1101);
+        assert_eq!(board.neighbor_bitmask((2, 2)).count_ones(), live_neighbours(&board, (2, 2)) as u32);
+        assert_eq!(board.neighbor_bitmask((5, 5)), 0);
+
+        let mut board = Board::from_live_coords(4, 4, [(3, 0), (1, 0)]).unwrap();
+        assert_eq!(board.neighbor_bitmask((0, 0)), 0b0100_0100);
+        board.set_edges(EdgeBehavior::Bounded);
+        assert_eq!(board.neighbor_bitmask((0, 0)), 0b0000_0100);
+    }
+
+    #[test]
+    fn population_in() {
+        let mut board = Board::new(12, 8);
+        board.stamp(&Pattern::glider(), (1, 1));
+        board.stamp(&Pattern::glider(), (8, 4));
+        assert_eq!(board.population_in((0, 0), (12, 8)), 10);
+        assert_eq!(board.population_in((0, 0), (6, 6)), 5);
+        assert_eq!(board.population_in((8, 4), (3, 1)), 1);
+        // clipped to the board rather than panicking
+        assert_eq!(board.population_in((6, 3), (100, 100)), 5);
+        assert_eq!(board.population_in((usize::MAX, 2), (usize::MAX, 1)), 0);
+        assert_eq!(board.population_in((3, 3), (0, 5)), 0);
+    }
+
+    #[test]
+    fn step_stats() {
+        let mut board = Board::from_live_coords(5, 5, [(1, 2), (2, 2), (3, 2)]).unwrap();
+        assert_eq!(board.step_stats(), StepStats { population: 3, births: 0, deaths: 0 });
+        next_state(&mut board);
+        assert_eq!(board.step_stats(), StepStats { population: 3, births: 2, deaths: 2 });
+    }
 }
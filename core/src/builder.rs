@@ -0,0 +1,124 @@
+use alloc::vec::Vec;
+
+use crate::board::{Board, BoardError};
+use crate::grid::EdgeBehavior;
+use crate::pattern::Pattern;
+use crate::rules::Rules;
+use crate::simulation::Simulation;
+
+/// Fluent construction of a board together with the rules it evolves under:
+///
+/// ```
+/// # use game_of_life::builder::BoardBuilder;
+/// # use game_of_life::pattern::Pattern;
+/// # use game_of_life::rules::Rules;
+/// let simulation = BoardBuilder::new(80, 40)
+///     .toroidal()
+///     .rule(Rules::conway())
+///     .with_pattern(&Pattern::glider(), (10, 10))
+///     .build()
+///     .unwrap();
+/// assert_eq!(simulation.board().width(), 80);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BoardBuilder {
+    width: usize,
+    height: usize,
+    edges: EdgeBehavior,
+    rules: Rules,
+    patterns: Vec<(Pattern, (usize, usize))>,
+}
+
+impl BoardBuilder {
+    pub fn new(width: usize, height: usize) -> Self {
+        BoardBuilder {
+            width,
+            height,
+            edges: EdgeBehavior::Toroidal,
+            rules: Rules::conway(),
+            patterns: Vec::new(),
+        }
+    }
+
+    pub fn toroidal(self) -> Self {
+        self.edges(EdgeBehavior::Toroidal)
+    }
+
+    pub fn bounded(self) -> Self {
+        self.edges(EdgeBehavior::Bounded)
+    }
+
+    pub fn edges(mut self, edges: EdgeBehavior) -> Self {
+        self.edges = edges;
+        self
+    }
+
+    pub fn rule(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Stamps `pattern` with its top left corner at `at`, cells outside the board are clipped.
+    pub fn with_pattern(mut self, pattern: &Pattern, at: (usize, usize)) -> Self {
+        self.patterns.push((pattern.clone(), at));
+        self
+    }
+
+    pub fn build(self) -> Result<Simulation, BoardError> {
+        if self.width == 0 || self.height == 0 {
+            return Err(BoardError::ZeroSized);
+        }
+        let mut board = Board::new(self.width, self.height);
+        board.set_edges(self.edges);
+        for (pattern, at) in &self.patterns {
+            board.stamp(pattern, *at);
+        }
+        let mut simulation = Simulation::new(board);
+        simulation.set_rules(self.rules);
+        Ok(simulation)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{BoardError, Cell};
+    use crate::grid::EdgeBehavior;
+    use crate::pattern::Pattern;
+    use crate::rules::Rules;
+
+    use super::BoardBuilder;
+
+    #[test]
+    fn builds_configured_board() {
+        let simulation = BoardBuilder::new(8, 6)
+            .bounded()
+            .rule(Rules::highlife())
+            .with_pattern(&Pattern::glider(), (2, 1))
+            .build()
+            .unwrap();
+        let board = simulation.board();
+        assert_eq!((board.width(), board.height()), (8, 6));
+        assert_eq!(board.edges(), EdgeBehavior::Bounded);
+        assert_eq!(simulation.rules(), &Rules::highlife());
+        assert_eq!(board.iter().filter(|entry| entry.cell() == Cell::Alive).count(), 5);
+        assert_eq!(board[(3, 1)], Cell::Alive);
+    }
+
+    #[test]
+    fn clips_patterns() {
+        let simulation = BoardBuilder::new(4, 4)
+            .with_pattern(&Pattern::glider(), (2, 1))
+            .with_pattern(&Pattern::glider(), (10, 10))
+            .build()
+            .unwrap();
+        let live = simulation.board().iter().filter(|entry| entry.cell().is_alive()).count();
+        assert_eq!(live, 3);
+    }
+
+    #[test]
+    fn rejects_zero_size() {
+        assert_eq!(BoardBuilder::new(0, 4).build().unwrap_err(), BoardError::ZeroSized);
+        assert_eq!(BoardBuilder::new(4, 0).build().unwrap_err(), BoardError::ZeroSized);
+    }
+}
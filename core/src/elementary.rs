@@ -0,0 +1,96 @@
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+use crate::board::{Board, Cell};
+use crate::grid::EdgeBehavior;
+
+/// One of Wolfram's 256 elementary automata: a row of cells where each cell's next state
+/// depends on itself and its two neighbours, bit `4 * left + 2 * center + right` of the number.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Rule1D(pub u8);
+
+impl Rule1D {
+    pub fn next(self, left: bool, center: bool, right: bool) -> bool {
+        let index = (left as u8) << 2 | (center as u8) << 1 | right as u8;
+        self.0 >> index & 1 == 1
+    }
+}
+
+impl Display for Rule1D {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Rule {}", self.0)
+    }
+}
+
+/// Writes the generation after the one in row `y` into the row below it, scrolling the board up
+/// by a row first when `y` is the last one. Returns the row written to. The ends of a row wrap
+/// around unless the board is bounded, where cells beyond them are dead.
+pub fn step_1d(board: &mut Board, rule: Rule1D, y: usize) -> usize {
+    let (width, height) = (board.width(), board.height());
+    let y = y.min(height - 1);
+    let (from, next) = if y + 1 < height {
+        (y, y + 1)
+    } else if height == 1 {
+        (0, 0)
+    } else {
+        for row in 1..height {
+            for x in 0..width {
+                board[(x, row - 1)] = board[(x, row)];
+            }
+        }
+        (height - 2, height - 1)
+    };
+    let wrap = board.edges() != EdgeBehavior::Bounded;
+    let alive = |board: &Board, x: usize| board[(x, from)].is_alive();
+    let row: Vec<bool> = (0..width)
+        .map(|x| {
+            let left = match x.checked_sub(1) {
+                Some(left) => alive(board, left),
+                None => wrap && alive(board, width - 1),
+            };
+            let right = match x + 1 {
+                right if right < width => alive(board, right),
+                _ => wrap && alive(board, 0),
+            };
+            rule.next(left, alive(board, x), right)
+        })
+        .collect();
+    for (x, alive) in row.into_iter().enumerate() {
+        board[(x, next)] = if alive { Cell::Alive } else { Cell::Dead };
+    }
+    next
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::board::Board;
+    use crate::grid::EdgeBehavior;
+
+    use super::{step_1d, Rule1D};
+
+    #[test]
+    fn rule_bits() {
+        let rule = Rule1D(30);
+        let next: Vec<_> = (0..8u8).map(|index| rule.next(index & 4 != 0, index & 2 != 0, index & 1 != 0)).collect();
+        assert_eq!(next, [false, true, true, true, true, false, false, false]);
+        assert_eq!(Rule1D(110).to_string(), "Rule 110");
+    }
+
+    #[test]
+    fn rows_grow_downwards_then_scroll() {
+        let mut board = Board::from_live_coords(7, 3, [(3, 0)]).unwrap();
+        board.set_edges(EdgeBehavior::Bounded);
+        assert_eq!(step_1d(&mut board, Rule1D(90), 0), 1);
+        assert_eq!(step_1d(&mut board, Rule1D(90), 1), 2);
+        assert_eq!(board.to_string(), "XXXOXXX\nXXOXOXX\nXOXXXOX\n");
+        assert_eq!(step_1d(&mut board, Rule1D(90), 2), 2);
+        assert_eq!(board.to_string(), "XXOXOXX\nXOXXXOX\nOXOXOXO\n");
+        // the ends meet on a wrapping board
+        let mut board = Board::from_live_coords(4, 2, [(0, 0)]).unwrap();
+        step_1d(&mut board, Rule1D(90), 0);
+        assert_eq!(board.to_string(), "OXXX\nXOXO\n");
+    }
+}
@@ -0,0 +1,100 @@
+use core::ops::{Index, IndexMut};
+
+use crate::board::Cell;
+use crate::grid::Grid;
+
+/// Board with dimensions known at compile time, stored inline without heap allocation.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct FixedBoard<const W: usize, const H: usize> {
+    inner: [[Cell; W]; H],
+}
+
+impl<const W: usize, const H: usize> FixedBoard<W, H> {
+    pub const fn new() -> Self {
+        assert!(W > 0 && H > 0, "board cannot be zero sized");
+        FixedBoard {
+            inner: [[Cell::Dead; W]; H],
+        }
+    }
+
+    pub fn width(&self) -> usize { W }
+    pub fn height(&self) -> usize { H }
+
+    pub fn check_index(&self, (x, y): (usize, usize)) -> bool {
+        x < W && y < H
+    }
+}
+
+impl<const W: usize, const H: usize> Default for FixedBoard<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> Grid for FixedBoard<W, H> {
+    fn width(&self) -> usize { W }
+    fn height(&self) -> usize { H }
+    fn get(&self, index: (usize, usize)) -> Cell { self[index] }
+    fn set(&mut self, index: (usize, usize), cell: Cell) { self[index] = cell }
+}
+
+impl<const W: usize, const H: usize> Index<(usize, usize)> for FixedBoard<W, H> {
+    type Output = Cell;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        assert!(x < W, "x index {} is out of bound in width {}", x, W);
+        assert!(y < H, "y index {} is out of bound in height {}", y, H);
+        &self.inner[y][x]
+    }
+}
+
+impl<const W: usize, const H: usize> IndexMut<(usize, usize)> for FixedBoard<W, H> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
+        assert!(x < W, "x index {} is out of bound in width {}", x, W);
+        assert!(y < H, "y index {} is out of bound in height {}", y, H);
+        &mut self.inner[y][x]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{Board, Cell};
+    use crate::grid::Grid;
+    use crate::logic::next_state;
+
+    use super::FixedBoard;
+
+    #[test]
+    fn fixed_board_index() {
+        let mut board = FixedBoard::<3, 2>::new();
+        board[(2, 1)] = Cell::Alive;
+        assert_eq!(board.width(), 3);
+        assert_eq!(board.height(), 2);
+        assert_eq!(Cell::Alive, board[(2, 1)]);
+        assert_eq!(Cell::Dead, board.get((1, 1)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn fixed_board_index_not_valid() {
+        let board = FixedBoard::<3, 2>::new();
+        let _cell = board[(3, 0)];
+    }
+
+    #[test]
+    fn fixed_board_evolves_like_board() {
+        let mut fixed = FixedBoard::<5, 5>::new();
+        let mut board = Board::new(5, 5);
+        for x in 1..4 {
+            fixed[(x, 2)] = Cell::Alive;
+            board[(x, 2)] = Cell::Alive;
+        }
+        for _ in 0..4 {
+            assert_eq!(next_state(&mut fixed), next_state(&mut board));
+            for entry in board.iter() {
+                assert_eq!(fixed[entry.index()], entry.cell());
+            }
+        }
+    }
+}
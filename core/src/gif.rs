@@ -0,0 +1,204 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::board::Board;
+
+// black for dead cells and white for live ones, padded to the 4 colors of the smallest LZW code size
+const PALETTE: [u8; 12] = [0, 0, 0, 255, 255, 255, 0, 0, 0, 0, 0, 0];
+const MIN_CODE_SIZE: u8 = 2;
+const MAX_CODE: u16 = 4096;
+
+/// Animated GIF of `frames` drawn with cells `scale` pixels wide, white on black, looping
+/// forever with `delay` between frames in steps of 10ms. `None` without frames, for frames
+/// of different sizes and for images larger than a GIF can be.
+pub fn to_gif(frames: &[Board], scale: usize, delay: Duration) -> Option<Vec<u8>> {
+    let first = frames.first()?;
+    let (width, height) = (first.width(), first.height());
+    if frames.iter().any(|frame| (frame.width(), frame.height()) != (width, height)) {
+        return None;
+    }
+    let pixels_wide = u16::try_from(width.checked_mul(scale)?).ok()?;
+    let pixels_high = u16::try_from(height.checked_mul(scale)?).ok()?;
+    let centiseconds = u16::try_from(delay.as_millis() / 10).unwrap_or(u16::MAX);
+
+    let mut gif = Vec::new();
+    gif.extend_from_slice(b"GIF89a");
+    gif.extend_from_slice(&pixels_wide.to_le_bytes());
+    gif.extend_from_slice(&pixels_high.to_le_bytes());
+    // a global color table of 2^(1 + 1) colors, 2 bits per primary color
+    gif.extend_from_slice(&[0x91, 0, 0]);
+    gif.extend_from_slice(&PALETTE);
+    // the NETSCAPE2.0 extension repeats the animation forever
+    gif.extend_from_slice(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+    for frame in frames {
+        gif.extend_from_slice(&[0x21, 0xf9, 0x04, 0x00]);
+        gif.extend_from_slice(&centiseconds.to_le_bytes());
+        gif.extend_from_slice(&[0x00, 0x00]);
+        gif.push(0x2c);
+        gif.extend_from_slice(&[0, 0, 0, 0]);
+        gif.extend_from_slice(&pixels_wide.to_le_bytes());
+        gif.extend_from_slice(&pixels_high.to_le_bytes());
+        gif.push(0x00);
+        gif.push(MIN_CODE_SIZE);
+        let mut pixels = Vec::with_capacity(pixels_wide as usize * pixels_high as usize);
+        for row in frame.rows() {
+            let line: Vec<u8> = row.iter()
+                .flat_map(|cell| core::iter::repeat_n(cell.is_alive() as u8, scale))
+                .collect();
+            for _ in 0..scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+        for block in lzw(&pixels).chunks(255) {
+            gif.push(block.len() as u8);
+            gif.extend_from_slice(block);
+        }
+        gif.push(0x00);
+    }
+    gif.push(0x3b);
+    Some(gif)
+}
+
+// variable width LZW as GIF uses it, codes packed from the least significant bit
+fn lzw(pixels: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << MIN_CODE_SIZE;
+    let end = clear + 1;
+    let mut out = BitWriter::default();
+    let mut table = BTreeMap::new();
+    let mut width = MIN_CODE_SIZE + 1;
+    let mut next = end + 1;
+    out.write(clear, width);
+    let mut pixels = pixels.iter();
+    if let Some(&first) = pixels.next() {
+        let mut prefix = first as u16;
+        for &pixel in pixels {
+            if let Some(&code) = table.get(&(prefix, pixel)) {
+                prefix = code;
+                continue;
+            }
+            out.write(prefix, width);
+            if next == MAX_CODE {
+                // a full table starts over rather than going on with the codes it has
+                out.write(clear, width);
+                table.clear();
+                width = MIN_CODE_SIZE + 1;
+                next = end + 1;
+            } else {
+                table.insert((prefix, pixel), next);
+                if next == 1 << width {
+                    width += 1;
+                }
+                next += 1;
+            }
+            prefix = pixel as u16;
+        }
+        out.write(prefix, width);
+    }
+    out.write(end, width);
+    out.finish()
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    pending: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u8) {
+        self.pending |= (code as u32) << self.bits;
+        self.bits += width;
+        while self.bits >= 8 {
+            self.bytes.push(self.pending as u8);
+            self.pending >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.pending as u8);
+        }
+        self.bytes
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::time::Duration;
+
+    use crate::board::Board;
+
+    use super::{lzw, to_gif, MAX_CODE};
+
+    // reference decoder of the codes `lzw` writes
+    fn unlzw(bytes: &[u8]) -> Vec<u8> {
+        let (clear, end) = (4u16, 5u16);
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let reset = |table: &mut Vec<Vec<u8>>| {
+            table.clear();
+            table.extend((0..6).map(|code| Vec::from([code as u8])));
+        };
+        reset(&mut table);
+        let (mut width, mut position) = (3u32, 0usize);
+        let mut previous: Option<Vec<u8>> = None;
+        let mut out = Vec::new();
+        loop {
+            let code = (0..width)
+                .map(|bit| ((bytes[(position + bit as usize) / 8] >> ((position + bit as usize) % 8)) & 1) as u16)
+                .enumerate()
+                .fold(0, |code, (bit, value)| code | value << bit);
+            position += width as usize;
+            if code == clear {
+                reset(&mut table);
+                width = 3;
+                previous = None;
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+            let entry = match (table.get(code as usize), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(previous)) => [previous.as_slice(), &previous[..1]].concat(),
+                (None, None) => panic!("code {} before any other", code),
+            };
+            if let Some(previous) = previous {
+                if table.len() < MAX_CODE as usize {
+                    table.push([previous.as_slice(), &entry[..1]].concat());
+                    if table.len() == 1 << width && width < 12 {
+                        width += 1;
+                    }
+                }
+            }
+            out.extend_from_slice(&entry);
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trip() {
+        let board = Board::random(97, 83, 0.4, 5);
+        let pixels: Vec<u8> = board.iter().map(|entry| entry.cell().is_alive() as u8).collect();
+        assert_eq!(unlzw(&lzw(&pixels)), pixels);
+        let runs = [0u8; 20_000];
+        assert_eq!(unlzw(&lzw(&runs)), runs);
+        assert!(unlzw(&lzw(&[])).is_empty());
+    }
+
+    #[test]
+    fn gif_frames() {
+        let blinker = Board::from_live_coords(5, 5, [(1, 2), (2, 2), (3, 2)]).unwrap();
+        let gif = to_gif(&[blinker.clone(), blinker.clone()], 2, Duration::from_millis(64)).unwrap();
+        assert!(gif.starts_with(b"GIF89a\x0a\x00\x0a\x00"));
+        assert_eq!(gif.last(), Some(&0x3b));
+        assert_eq!(gif.windows(3).filter(|window| window == b"\x21\xf9\x04").count(), 2);
+        assert!(to_gif(&[], 2, Duration::ZERO).is_none());
+        assert!(to_gif(&[blinker, Board::new(4, 4)], 2, Duration::ZERO).is_none());
+        assert!(to_gif(&[Board::new(70_000, 1)], 1, Duration::ZERO).is_none());
+    }
+}
@@ -0,0 +1,91 @@
+use core::fmt::{Display, Formatter};
+
+use crate::board::Cell;
+
+/// What lies beyond the board's edges when counting neighbours.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdgeBehavior {
+    /// Opposite edges are glued together.
+    #[default]
+    Toroidal,
+    /// Everything outside the board is permanently dead.
+    Bounded,
+    /// Glued like the torus, but crossing the left or right edge mirrors the row, a Klein bottle.
+    Klein,
+    /// Crossing any edge mirrors the other coordinate, a projective plane.
+    Projective,
+}
+
+impl EdgeBehavior {
+    /// Whether crossing an edge mirrors the position along it.
+    pub fn is_twisted(&self) -> bool {
+        matches!(self, EdgeBehavior::Klein | EdgeBehavior::Projective)
+    }
+}
+
+impl Display for EdgeBehavior {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EdgeBehavior::Toroidal => write!(f, "toroidal"),
+            EdgeBehavior::Bounded => write!(f, "bounded"),
+            EdgeBehavior::Klein => write!(f, "klein"),
+            EdgeBehavior::Projective => write!(f, "projective"),
+        }
+    }
+}
+
+/// How cells are arranged, which decides their neighbours.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Topology {
+    /// Square cells with the 8 Moore neighbours.
+    #[default]
+    Square,
+    /// Hexagons with 6 neighbours, laid out in rows with the odd ones shifted right by half
+    /// a cell. Wrapping boards need an even height for the rows to line up across the edge.
+    Hex,
+}
+
+impl Display for Topology {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Topology::Square => write!(f, "square"),
+            Topology::Hex => write!(f, "hex"),
+        }
+    }
+}
+
+pub trait Grid {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    fn get(&self, index: (usize, usize)) -> Cell;
+    fn set(&mut self, index: (usize, usize), cell: Cell);
+
+    fn edges(&self) -> EdgeBehavior {
+        EdgeBehavior::Toroidal
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::Square
+    }
+
+    /// Position of the row and column where the board wraps around (or ends, when
+    /// bounded), `(0, 0)` being the usual array edges.
+    fn seam(&self) -> (usize, usize) {
+        (0, 0)
+    }
+
+    /// Whether `index` belongs to the board's shape, cells outside of it are always dead
+    /// and never count as neighbours.
+    fn in_shape(&self, _index: (usize, usize)) -> bool {
+        true
+    }
+
+    /// Whether every cell is known to be `Dead`, which lets a step skip the scan.
+    fn is_settled(&self) -> bool {
+        false
+    }
+
+    fn set_settled(&mut self, _settled: bool) {}
+}
@@ -0,0 +1,119 @@
+use alloc::vec::Vec;
+
+use crate::board::Board;
+use crate::logic::next_state_with;
+use crate::rules::Rules;
+
+/// Periodic keyframes of past generations. Life is not reversible, so an earlier
+/// generation is rebuilt by replaying forward from the nearest keyframe before it.
+#[derive(Clone, Debug)]
+pub struct History {
+    interval: u64,
+    capacity: usize,
+    keyframes: Vec<(u64, Board)>,
+}
+
+impl History {
+    pub fn new(interval: u64, capacity: usize) -> Self {
+        assert!(interval > 0, "keyframe interval cannot be zero");
+        assert!(capacity > 0, "history capacity cannot be zero");
+        History {
+            interval,
+            capacity,
+            keyframes: Vec::new(),
+        }
+    }
+
+    /// Called after every generation, keeps a keyframe every `interval` generations.
+    pub fn record(&mut self, generation: u64, board: &Board) {
+        if generation.is_multiple_of(self.interval) {
+            self.keyframe(generation, board);
+        }
+    }
+
+    /// Stores `board` as the state of `generation`, e.g. after an edit. Keyframes of
+    /// later generations are dropped as they no longer follow from it.
+    pub fn keyframe(&mut self, generation: u64, board: &Board) {
+        self.keyframes.retain(|(kept, _)| *kept < generation);
+        self.keyframes.push((generation, board.clone()));
+        if self.keyframes.len() > self.capacity {
+            self.keyframes.remove(0);
+        }
+    }
+
+    pub fn reconstruct(&self, generation: u64, rules: &Rules) -> Option<Board> {
+        let (start, keyframe) = self.keyframes.iter()
+            .rev()
+            .find(|(kept, _)| *kept <= generation)?;
+        let mut board = keyframe.clone();
+        for replayed in *start..generation {
+            next_state_with(&mut board, &rules.emulated(replayed));
+        }
+        Some(board)
+    }
+
+    pub fn clear(&mut self) {
+        self.keyframes.clear();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{Board, Cell};
+    use crate::logic::next_state;
+    use crate::rules::Rules;
+
+    use super::History;
+
+    fn glider() -> Board {
+        let mut board = Board::new(8, 8);
+        for index in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            board[index] = Cell::Alive;
+        }
+        board
+    }
+
+    #[test]
+    fn reconstructs_every_generation() {
+        let mut history = History::new(3, 16);
+        let mut board = glider();
+        let mut generations = vec![board.clone()];
+        history.keyframe(0, &board);
+        for generation in 1..=10 {
+            next_state(&mut board);
+            history.record(generation, &board);
+            generations.push(board.clone());
+        }
+        for (generation, expected) in generations.iter().enumerate() {
+            assert_eq!(history.reconstruct(generation as u64, &Rules::conway()).as_ref(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn edit_replaces_later_keyframes() {
+        let mut history = History::new(2, 16);
+        let mut board = glider();
+        history.keyframe(0, &board);
+        for generation in 1..=4 {
+            next_state(&mut board);
+            history.record(generation, &board);
+        }
+        let mut edited = history.reconstruct(1, &Rules::conway()).unwrap();
+        edited[(7, 7)] = Cell::Born;
+        history.keyframe(1, &edited);
+        assert_eq!(history.reconstruct(1, &Rules::conway()), Some(edited.clone()));
+        next_state(&mut edited);
+        assert_eq!(history.reconstruct(2, &Rules::conway()), Some(edited));
+    }
+
+    #[test]
+    fn nothing_before_first_keyframe() {
+        let mut history = History::new(2, 2);
+        history.keyframe(5, &glider());
+        assert_eq!(history.reconstruct(4, &Rules::conway()), None);
+        history.keyframe(6, &glider());
+        history.keyframe(7, &glider());
+        assert_eq!(history.reconstruct(5, &Rules::conway()), None);
+    }
+}
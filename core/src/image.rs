@@ -0,0 +1,195 @@
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use crate::board::{Board, Cell};
+use crate::png::{decode_png, is_png};
+
+/// Longest side of a board read from an image, larger images are scaled down.
+pub const MAX_IMAGE_SIDE: usize = 512;
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(std::io::Error),
+    Format(&'static str),
+}
+
+impl Display for ImageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::Io(err) => write!(f, "could not read image: {}", err),
+            ImageError::Format(reason) => write!(f, "unsupported image: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<std::io::Error> for ImageError {
+    fn from(err: std::io::Error) -> Self {
+        ImageError::Io(err)
+    }
+}
+
+impl Board {
+    /// Board from a PNG or a grayscale or color Netpbm image (`.pgm` / `.ppm`, plain or binary)
+    /// where pixels darker than `threshold` are alive, transparent ones count as white.
+    /// Images with a side longer than `MAX_IMAGE_SIDE` are scaled down with nearest-neighbour
+    /// sampling first.
+    pub fn from_image(path: &Path, threshold: u8) -> Result<Board, ImageError> {
+        let (width, height, luma) = decode(&std::fs::read(path)?)?;
+        Ok(from_luma(width, height, &luma, threshold))
+    }
+}
+
+fn from_luma(width: usize, height: usize, luma: &[u8], threshold: u8) -> Board {
+    let scale = (width.max(height) as f64 / MAX_IMAGE_SIDE as f64).max(1.0);
+    let board_width = ((width as f64 / scale) as usize).max(1);
+    let board_height = ((height as f64 / scale) as usize).max(1);
+    let mut board = Board::new(board_width, board_height);
+    for y in 0..board_height {
+        for x in 0..board_width {
+            let (sx, sy) = ((x as f64 * scale) as usize, (y as f64 * scale) as usize);
+            if luma[sy * width + sx] < threshold {
+                board[(x, y)] = Cell::Alive;
+            }
+        }
+    }
+    board
+}
+
+/// Width, height and 8 bit luma of a PNG or Netpbm image, told apart by their first bytes.
+fn decode(bytes: &[u8]) -> Result<(usize, usize, Vec<u8>), ImageError> {
+    if is_png(bytes) { decode_png(bytes) } else { decode_netpbm(bytes) }
+}
+
+/// Width, height and 8 bit luma of a P2, P3, P5 or P6 image.
+fn decode_netpbm(bytes: &[u8]) -> Result<(usize, usize, Vec<u8>), ImageError> {
+    let mut header = Header { bytes, position: 0 };
+    let magic = header.token().ok_or(ImageError::Format("empty file"))?;
+    let (channels, binary) = match magic {
+        b"P2" => (1, false),
+        b"P3" => (3, false),
+        b"P5" => (1, true),
+        b"P6" => (3, true),
+        _ => return Err(ImageError::Format("only PNG, PGM and PPM images are supported")),
+    };
+    let width = header.number()?;
+    let height = header.number()?;
+    let max_value = header.number()?;
+    if width == 0 || height == 0 || !(1..=u16::MAX as usize).contains(&max_value) {
+        return Err(ImageError::Format("invalid header"));
+    }
+    let samples = width.checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(channels))
+        .ok_or(ImageError::Format("image is too large"))?;
+    let values: Vec<usize> = if binary {
+        // a single whitespace separates the header from the raster
+        let raster = bytes.get(header.position + 1..).unwrap_or_default();
+        let sample_size = if max_value > u8::MAX as usize { 2 } else { 1 };
+        if raster.len() < samples * sample_size {
+            return Err(ImageError::Format("truncated pixel data"));
+        }
+        raster.chunks(sample_size)
+            .take(samples)
+            .map(|sample| sample.iter().fold(0, |value, &byte| value << 8 | byte as usize))
+            .collect()
+    } else {
+        (0..samples).map(|_| header.number()).collect::<Result<_, _>>()?
+    };
+    let luma = values.chunks(channels)
+        .map(|pixel| {
+            let gray = match pixel {
+                [r, g, b] => (299 * r + 587 * g + 114 * b) / 1000,
+                _ => pixel[0],
+            };
+            (gray.min(max_value) * u8::MAX as usize / max_value) as u8
+        })
+        .collect();
+    Ok((width, height, luma))
+}
+
+struct Header<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Header<'a> {
+    /// Next whitespace separated token, skipping `#` comments.
+    fn token(&mut self) -> Option<&'a [u8]> {
+        loop {
+            match self.bytes.get(self.position)? {
+                b'#' => {
+                    while self.bytes.get(self.position).is_some_and(|&byte| byte != b'\n') {
+                        self.position += 1;
+                    }
+                }
+                byte if byte.is_ascii_whitespace() => self.position += 1,
+                _ => break,
+            }
+        }
+        let start = self.position;
+        while self.bytes.get(self.position).is_some_and(|byte| !byte.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+        Some(&self.bytes[start..self.position])
+    }
+
+    fn number(&mut self) -> Result<usize, ImageError> {
+        self.token()
+            .and_then(|token| std::str::from_utf8(token).ok())
+            .and_then(|token| token.parse().ok())
+            .ok_or(ImageError::Format("expected a number"))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Board;
+
+    use super::{decode, decode_netpbm, from_luma, ImageError, MAX_IMAGE_SIDE};
+
+    #[test]
+    fn plain_graymap() {
+        let pgm = b"P2\n# smiley\n3 2\n15\n0 15 0\n15 0 15\n";
+        let (width, height, luma) = decode_netpbm(pgm).unwrap();
+        assert_eq!((width, height), (3, 2));
+        assert_eq!(luma, [0, 255, 0, 255, 0, 255]);
+        let board = from_luma(width, height, &luma, 128);
+        assert_eq!(board, Board::from_live_coords(3, 2, [(0, 0), (2, 0), (1, 1)]).unwrap());
+    }
+
+    #[test]
+    fn binary_pixmap() {
+        let mut ppm = b"P6 2 1 255\n".to_vec();
+        ppm.extend([255, 255, 255, 10, 20, 30]);
+        let (_, _, luma) = decode_netpbm(&ppm).unwrap();
+        assert_eq!(luma, [255, 18]);
+        assert!(matches!(decode_netpbm(b"P6 2 1 255\n\x01\x02"), Err(ImageError::Format(_))));
+        assert!(matches!(decode_netpbm(b"\x89PNG"), Err(ImageError::Format(_))));
+        assert!(matches!(decode_netpbm(b"P5 18446744073709551615 2 255\n"), Err(ImageError::Format(_))));
+    }
+
+    #[test]
+    fn formats_by_their_first_bytes() {
+        assert_eq!(decode(b"P2 1 1 1 0").unwrap(), (1, 1, vec![0]));
+        let message = |bytes: &[u8]| decode(bytes).unwrap_err().to_string();
+        assert_eq!(message(b"\x89PNG\r\n\x1a\n"), "unsupported image: truncated PNG image");
+        assert_eq!(message(b"GIF89a"), "unsupported image: only PNG, PGM and PPM images are supported");
+    }
+
+    #[test]
+    fn huge_images_are_scaled_down() {
+        let (width, height) = (MAX_IMAGE_SIDE * 4, MAX_IMAGE_SIDE);
+        let luma: Vec<u8> = (0..width * height).map(|index| if index % width < width / 2 { 0 } else { 255 }).collect();
+        let board = from_luma(width, height, &luma, 128);
+        assert_eq!((board.width(), board.height()), (MAX_IMAGE_SIDE, MAX_IMAGE_SIDE / 4));
+        assert_eq!(board.live_count(), MAX_IMAGE_SIDE / 2 * MAX_IMAGE_SIDE / 4);
+    }
+
+    #[test]
+    fn missing_file() {
+        let result = Board::from_image(std::path::Path::new("/nonexistent/board.pgm"), 128);
+        assert!(matches!(result, Err(ImageError::Io(_))));
+    }
+}
@@ -13,5 +13,33 @@
 
 
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+pub mod bit_board;
 pub mod board;
-pub mod logic;
\ No newline at end of file
+pub mod builder;
+pub mod elementary;
+pub mod fixed_board;
+pub mod gif;
+pub mod grid;
+pub mod history;
+pub mod life_history;
+#[cfg(feature = "image")]
+pub mod image;
+pub mod logic;
+pub mod mask;
+pub mod pattern;
+#[cfg(feature = "image")]
+mod png;
+pub mod rle;
+pub mod rng;
+pub mod rules;
+#[cfg(feature = "serde")]
+mod serialization;
+#[cfg(feature = "serde")]
+pub mod session;
+pub mod simulation;
+pub mod soup;
+pub mod symmetry;
\ No newline at end of file
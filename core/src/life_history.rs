@@ -0,0 +1,199 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::board::Board;
+use crate::pattern::{Pattern, PatternError};
+use crate::rle::{check_area, read_runs};
+
+/// States of the LifeHistory rule, numbered like its RLE alphabet `.ABCDEF`.
+/// They only annotate a pattern, its live cells evolve by B3/S23 as usual.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum HistoryState {
+    #[default]
+    Off = 0,
+    On = 1,
+    /// Dead cell that was alive at some point.
+    Ghost = 2,
+    MarkedOn = 3,
+    MarkedOff = 4,
+    Start = 5,
+    /// Dead cell outlining a region, it does not block births here.
+    Boundary = 6,
+}
+
+impl HistoryState {
+    pub fn from_tag(tag: char) -> Option<Self> {
+        let state = match tag {
+            '.' | 'b' => HistoryState::Off,
+            'A' | 'o' => HistoryState::On,
+            'B' => HistoryState::Ghost,
+            'C' => HistoryState::MarkedOn,
+            'D' => HistoryState::MarkedOff,
+            'E' => HistoryState::Start,
+            'F' => HistoryState::Boundary,
+            _ => return None,
+        };
+        Some(state)
+    }
+
+    pub fn is_alive(&self) -> bool {
+        matches!(self, HistoryState::On | HistoryState::MarkedOn | HistoryState::Start)
+    }
+
+    /// The state once the cell is `alive`, marked cells stay marked and dead cells leave a ghost.
+    pub fn with_liveness(self, alive: bool) -> Self {
+        match (self, alive) {
+            (HistoryState::MarkedOn | HistoryState::MarkedOff, true) => HistoryState::MarkedOn,
+            (HistoryState::MarkedOn | HistoryState::MarkedOff, false) => HistoryState::MarkedOff,
+            (HistoryState::Start, true) => HistoryState::Start,
+            (_, true) => HistoryState::On,
+            (HistoryState::Off | HistoryState::Boundary, false) => self,
+            (_, false) => HistoryState::Ghost,
+        }
+    }
+}
+
+/// Reads an RLE pattern in the LifeHistory alphabet, sized to the bounding box of its non-empty cells.
+pub fn from_history_rle(s: &str) -> Result<HistoryLayer, PatternError> {
+    let mut cells = Vec::new();
+    let mut right = 0;
+    read_runs(s, |tag, (x, y), count| match HistoryState::from_tag(tag) {
+        Some(HistoryState::Off) => Ok(()),
+        Some(state) => {
+            check_area(&mut right, (x, y), count)?;
+            cells.extend((x..x + count).map(|x| ((x, y), state)));
+            Ok(())
+        }
+        None => Err(PatternError::Unexpected(tag)),
+    })?;
+    let width = cells.iter().map(|&((x, _), _)| x + 1).max().unwrap_or(0);
+    let height = cells.iter().map(|&((_, y), _)| y + 1).max().unwrap_or(0);
+    let mut layer = HistoryLayer::new(width, height);
+    for (index, state) in cells {
+        layer.set(index, state);
+    }
+    Ok(layer)
+}
+
+/// Whether the `x = ...` header of an RLE pattern names the LifeHistory rule.
+pub fn is_history_rle(s: &str) -> bool {
+    s.lines()
+        .map(str::trim)
+        .find(|line| line.starts_with('x'))
+        .into_iter()
+        .flat_map(|header| header.split(','))
+        .filter_map(|item| item.split_once('='))
+        .any(|(key, value)| key.trim() == "rule" && value.trim().eq_ignore_ascii_case("LifeHistory"))
+}
+
+/// LifeHistory states of a board, kept in line with its live cells by `update`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct HistoryLayer {
+    width: usize,
+    height: usize,
+    states: Vec<HistoryState>,
+}
+
+impl HistoryLayer {
+    pub fn new(width: usize, height: usize) -> Self {
+        HistoryLayer {
+            width,
+            height,
+            states: vec![HistoryState::Off; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize { self.width }
+    pub fn height(&self) -> usize { self.height }
+
+    pub fn get(&self, (x, y): (usize, usize)) -> HistoryState {
+        if x < self.width && y < self.height {
+            self.states[y * self.width + x]
+        } else {
+            HistoryState::Off
+        }
+    }
+
+    pub fn set(&mut self, (x, y): (usize, usize), state: HistoryState) {
+        assert!(x < self.width && y < self.height, "({}, {}) is outside of the layer", x, y);
+        self.states[y * self.width + x] = state;
+    }
+
+    /// Live cells of the layer.
+    pub fn pattern(&self) -> Pattern {
+        let alive = self.states.iter()
+            .enumerate()
+            .filter(|(_, state)| state.is_alive())
+            .map(|(index, _)| (index % self.width, index / self.width));
+        Pattern::from_cells(alive)
+    }
+
+    /// Copies the non-empty cells of `other` with its top left corner at `(x, y)`,
+    /// cells falling outside the layer are clipped.
+    pub fn stamp(&mut self, other: &HistoryLayer, (x, y): (usize, usize)) {
+        for (index, &state) in other.states.iter().enumerate() {
+            let (cx, cy) = (x + index % other.width, y + index / other.width);
+            if state != HistoryState::Off && cx < self.width && cy < self.height {
+                self.set((cx, cy), state);
+            }
+        }
+    }
+
+    /// Follows the live cells of `board` after it was stepped or edited.
+    pub fn update(&mut self, board: &Board) {
+        assert_eq!((self.width, self.height), (board.width(), board.height()), "layer and board sizes differ");
+        for (index, state) in self.states.iter_mut().enumerate() {
+            let alive = board[(index % self.width, index / self.width)].is_alive();
+            *state = state.with_liveness(alive);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{Board, Cell};
+    use crate::pattern::PatternError;
+
+    use super::{from_history_rle, is_history_rle, HistoryLayer, HistoryState};
+
+    #[test]
+    fn parse_history_rle() {
+        let rle = "x = 5, y = 2, rule = LifeHistory\n.AB$2CDEF!";
+        assert!(is_history_rle(rle));
+        let layer = from_history_rle(rle).unwrap();
+        assert_eq!((layer.width(), layer.height()), (5, 2));
+        assert_eq!(layer.get((0, 0)), HistoryState::Off);
+        assert_eq!(layer.get((1, 0)), HistoryState::On);
+        assert_eq!(layer.get((2, 0)), HistoryState::Ghost);
+        assert_eq!(layer.get((1, 1)), HistoryState::MarkedOn);
+        assert_eq!(layer.get((2, 1)), HistoryState::MarkedOff);
+        assert_eq!(layer.get((3, 1)), HistoryState::Start);
+        assert_eq!(layer.get((4, 1)), HistoryState::Boundary);
+        assert_eq!(layer.pattern().cells(), &[(1, 0), (0, 1), (1, 1), (3, 1)]);
+        assert_eq!(from_history_rle("AG!"), Err(PatternError::Unexpected('G')));
+        assert!(!is_history_rle("x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!"));
+        assert!(!is_history_rle("bo$2bo$3o!"));
+    }
+
+    #[test]
+    fn states_follow_the_board() {
+        // a blinker with a marked tip, rotated by one step
+        let mut board = Board::new(5, 5);
+        let mut layer = HistoryLayer::new(5, 5);
+        layer.stamp(&from_history_rle("C2A!").unwrap(), (1, 2));
+        layer.set((0, 0), HistoryState::Boundary);
+        for x in 1..4 {
+            board[(x, 2)] = Cell::Alive;
+        }
+        board.step();
+        layer.update(&board);
+        assert_eq!(layer.get((1, 2)), HistoryState::MarkedOff);
+        assert_eq!(layer.get((2, 2)), HistoryState::On);
+        assert_eq!(layer.get((3, 2)), HistoryState::Ghost);
+        assert_eq!(layer.get((2, 1)), HistoryState::On);
+        assert_eq!(layer.get((0, 0)), HistoryState::Boundary);
+        assert_eq!(layer.get((4, 4)), HistoryState::Off);
+        assert_eq!(layer.get((9, 9)), HistoryState::Off);
+    }
+}
@@ -1,64 +1,348 @@
-use std::ops::{IndexMut};
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
 use crate::board::{Board, Cell};
+use crate::grid::{EdgeBehavior, Grid, Topology};
+use crate::mask::Mask;
+use crate::rng::Rng;
+use crate::rules::{Rules, StochasticRules};
 
 
-pub fn next_state(board: &mut Board) -> bool {
-    let snapshot = board.clone(); // TODO: optimize to not clone whole table, but remember some part
-    for entry in snapshot.iter() {
-        let cell = entry.cell();
-        let live_neighbours = count_live_neighbours(&snapshot, entry.index());
-        let new_cell = match cell {
-            Cell::Dead | Cell::Died if live_neighbours == 3 => {
-                Cell::Born
-            }
-            Cell::Alive | Cell::Born if live_neighbours < 2 => {
-                Cell::Died
-            }
-            Cell::Alive | Cell::Born if live_neighbours > 3 => {
-                Cell::Died
-            }
-            // move state further
-            Cell::Born => Cell::Alive,
-            Cell::Died => Cell::Dead,
-            _ => cell,
-        };
-        board[entry.index()] = new_cell;
+pub fn next_state<G: Grid + Clone + PartialEq>(board: &mut G) -> bool {
+    next_state_with(board, &Rules::conway())
+}
+
+pub fn next_state_with<G: Grid + Clone + PartialEq>(board: &mut G, rules: &Rules) -> bool {
+    step(board, rules, None)
+}
+
+/// Same as `next_state_with` but cells outside of `mask` keep their state, while
+/// still counting as neighbours of the cells inside.
+pub fn next_state_masked<G: Grid + Clone + PartialEq>(board: &mut G, rules: &Rules, mask: &Mask) -> bool {
+    assert_eq!((mask.width(), mask.height()), (board.width(), board.height()), "mask of another board size");
+    step(board, rules, Some(mask))
+}
+
+/// Same as `next_state_with` with births and survivals happening by chance, the same seed
+/// of `rng` reproduces the same generations.
+pub fn next_state_stochastic<G: Grid + Clone + PartialEq>(board: &mut G, rules: &StochasticRules, rng: &mut Rng) -> bool {
+    if board.is_settled() && rules.birth_prob[0] == 0.0 {
+        return false;
     }
+    evolve(board, None, |cell, live_neighbours| rules.next_cell(cell, live_neighbours, rng))
+}
+
+fn step<G: Grid + Clone + PartialEq>(board: &mut G, rules: &Rules, mask: Option<&Mask>) -> bool {
+    if board.is_settled() && !rules.is_birth(0) {
+        return false;
+    }
+    evolve(board, mask, |cell, live_neighbours| rules.next_cell(cell, live_neighbours))
+}
+
+fn evolve<G: Grid + Clone + PartialEq>(board: &mut G, mask: Option<&Mask>, mut next_cell: impl FnMut(Cell, u8) -> Cell) -> bool {
+    // a board's clone shares its cells, the first changed cell moves the board to a fresh buffer
+    let snapshot = board.clone();
+    let mut settled = true;
+    for y in 0..snapshot.height() {
+        for x in 0..snapshot.width() {
+            let cell = if !snapshot.in_shape((x, y)) {
+                Cell::Dead
+            } else if mask.is_none_or(|mask| mask.contains((x, y))) {
+                let live_neighbours = count_live_neighbours(&snapshot, (x, y));
+                next_cell(snapshot.get((x, y)), live_neighbours)
+            } else {
+                snapshot.get((x, y))
+            };
+            settled &= cell == Cell::Dead;
+            board.set((x, y), cell);
+        }
+    }
+    board.set_settled(settled);
     *board != snapshot
 }
 
+/// Cells that may change in the next generation. A cell can only change if it or
+/// one of its neighbours changed in the previous one, so everything else is skipped.
+#[derive(Clone, Debug)]
+pub struct ActiveCells {
+    cells: Vec<(usize, usize)>,
+    marked: Vec<bool>,
+    width: usize,
+    height: usize,
+}
+
+impl ActiveCells {
+    /// Every cell of `board`, the safe starting point for a board of unknown history.
+    pub fn all(board: &Board) -> Self {
+        ActiveCells {
+            cells: (0..board.height())
+                .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+                .collect(),
+            marked: vec![true; board.width() * board.height()],
+            width: board.width(),
+            height: board.height(),
+        }
+    }
+
+    pub fn len(&self) -> usize { self.cells.len() }
+    pub fn is_empty(&self) -> bool { self.cells.is_empty() }
+
+    /// Marks a changed cell and its neighbours, e.g. after editing the board by hand.
+    pub fn touch(&mut self, board: &Board, (x, y): (usize, usize)) {
+        self.insert((x, y));
+        for (dx, dy) in NEIGHBOUR_OFFSETS {
+            if let Some(index) = valid_neighbour_index(board, (x, y), dx, dy) {
+                self.insert(index);
+            }
+        }
+    }
+
+    fn insert(&mut self, (x, y): (usize, usize)) {
+        let marked = &mut self.marked[y * self.width + x];
+        if !*marked {
+            *marked = true;
+            self.cells.push((x, y));
+        }
+    }
+
+    fn clear(&mut self) {
+        for &(x, y) in &self.cells {
+            self.marked[y * self.width + x] = false;
+        }
+        self.cells.clear();
+    }
+}
+
+/// Same as `next_state_with` but only recomputes the `active` cells, which are
+/// updated for the following generation.
+pub fn next_state_active(board: &mut Board, rules: &Rules, active: &mut ActiveCells) -> bool {
+    assert_eq!((active.width, active.height), (board.width(), board.height()), "active cells of another board size");
+    let updates: Vec<_> = active.cells.iter()
+        .map(|&index| match board.in_shape(index) {
+            true => (index, rules.next_cell(board[index], count_live_neighbours(board, index))),
+            false => (index, Cell::Dead),
+        })
+        .filter(|&(index, cell)| board[index] != cell)
+        .collect();
+    active.clear();
+    for &(index, cell) in &updates {
+        board[index] = cell;
+    }
+    for &(index, _) in &updates {
+        active.touch(board, index);
+    }
+    !updates.is_empty()
+}
+
+/// Infinite iterator over the successive generations of a board, see `Board::generations`.
+#[derive(Clone, Debug)]
+pub struct Generations {
+    board: Board,
+    rules: Rules,
+    generation: u64,
+}
+
+impl Generations {
+    pub fn new(board: Board, rules: Rules) -> Self {
+        Generations { board, rules, generation: 0 }
+    }
+}
+
+impl Iterator for Generations {
+    type Item = Board;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_state_with(&mut self.board, &self.rules.emulated(self.generation));
+        self.generation += 1;
+        Some(self.board.clone())
+    }
+}
+
+/// Where a board's generations start repeating, by live cells only.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Cycle {
+    /// First generation of the cycle, the number of generations it took to stabilize.
+    pub start: u64,
+    /// 1 for still lifes and empty boards.
+    pub period: u64,
+}
+
+/// Steps `board` until its live cells repeat a state from at most `max_period` generations
+/// earlier, `None` if that does not happen within `max_gen` generations.
+/// States are compared by hash, so a collision could end the search early.
+pub fn detect_cycle(board: &mut Board, rules: &Rules, max_period: usize, max_gen: u64) -> Option<Cycle> {
+    assert!(max_period > 0, "cycles cannot have a zero period");
+    let mut recent = VecDeque::with_capacity(max_period);
+    recent.push_back(live_hash(board));
+    for generation in 1..=max_gen {
+        next_state_with(board, &rules.emulated(generation - 1));
+        let hash = live_hash(board);
+        if let Some(back) = recent.iter().rev().position(|&seen| seen == hash) {
+            let period = back as u64 + 1;
+            return Some(Cycle { start: generation - period, period });
+        }
+        if recent.len() == max_period {
+            recent.pop_front();
+        }
+        recent.push_back(hash);
+    }
+    None
+}
+
+/// How the live cells of a board move once they repeat.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Motion {
+    /// Still lifes, oscillators and empty boards repeat in place.
+    Stationary(Cycle),
+    /// The live cells repeat shifted by `(dx, dy)` every `period` generations.
+    Spaceship { period: u64, dx: isize, dy: isize },
+}
+
+/// Same as `detect_cycle`, but a state also repeats when its live cells reappear shifted, which
+/// tells spaceships apart from oscillators. Shifts take the shorter way around wrapping edges.
+pub fn classify_motion(board: &mut Board, rules: &Rules, max_period: usize, max_gen: u64) -> Option<Motion> {
+    assert!(max_period > 0, "cycles cannot have a zero period");
+    let mut recent = VecDeque::with_capacity(max_period);
+    recent.push_back(shape_hash(board));
+    for generation in 1..=max_gen {
+        next_state_with(board, &rules.emulated(generation - 1));
+        let (hash, (x, y)) = shape_hash(board);
+        if let Some(back) = recent.iter().rev().position(|&(seen, _)| seen == hash) {
+            let period = back as u64 + 1;
+            let (_, (x0, y0)) = recent[recent.len() - 1 - back];
+            let dx = shortest_shift(x as isize - x0 as isize, board.width());
+            let dy = shortest_shift(y as isize - y0 as isize, board.height());
+            return Some(match (dx, dy) {
+                (0, 0) => Motion::Stationary(Cycle { start: generation - period, period }),
+                _ => Motion::Spaceship { period, dx, dy },
+            });
+        }
+        if recent.len() == max_period {
+            recent.pop_front();
+        }
+        recent.push_back((hash, (x, y)));
+    }
+    None
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+// FNV-1a over the liveness of every cell
+fn live_hash(board: &Board) -> u64 {
+    board.iter().fold(FNV_OFFSET, |hash, entry| fnv(hash, entry.cell().is_alive() as u64))
+}
+
+// hash of the live cells relative to the corner of their bounding box, and that corner
+fn shape_hash(board: &Board) -> (u64, (usize, usize)) {
+    let Some(((x0, y0), (x1, y1))) = board.live_bounds() else {
+        return (FNV_OFFSET, (0, 0));
+    };
+    let hash = (y0..=y1)
+        .flat_map(|y| (x0..=x1).map(move |x| (x, y)))
+        .fold(fnv(FNV_OFFSET, (x1 - x0) as u64), |hash, index| fnv(hash, board[index].is_alive() as u64));
+    (hash, (x0, y0))
+}
+
+fn fnv(hash: u64, value: u64) -> u64 {
+    (hash ^ value).wrapping_mul(0x0100_0000_01b3)
+}
+
+// `offset` or the opposite way around a wrapping edge of `size` cells, whichever is shorter
+fn shortest_shift(offset: isize, size: usize) -> isize {
+    let size = size as isize;
+    let offset = offset.rem_euclid(size);
+    if offset > size / 2 { offset - size } else { offset }
+}
+
 pub fn resize(board: &mut Board, x: usize, y: usize) {
     let mut new_board = Board::new(x, y);
+    new_board.set_edges(board.edges());
+    new_board.set_seam(board.seam());
+    new_board.set_topology(board.topology());
+    // cells keep their exact state, born and died ones included
     board.iter()
-        .filter(|entry| entry.cell() == Cell::Alive && entry.x() < x && entry.y() < y)
-        .map(|entry| entry.index())
-        .for_each(|index| new_board.index_mut(index).flip());
+        .filter(|entry| entry.cell() != Cell::Dead && entry.x() < x && entry.y() < y)
+        .for_each(|entry| new_board.set(entry.index(), entry.cell()));
+    new_board.set_shape(board.shape().map(|shape| shape.scaled(x, y)));
     *board = new_board;
 }
 
 
-fn count_live_neighbours(board: &Board, (ux, uy): (usize, usize)) -> u8 {
-    let mut live_neighbours = 0;
-    let x = ux as isize;
-    let y = uy as isize;
-    for x in (x - 1)..=(x + 1) {
-        for y in (y - 1)..=(y + 1) {
-            if let Some(index) = valid_neighbour_index(board, (ux, uy), x, y) {
-                if board[index].is_alive() {
-                    live_neighbours += 1;
-                }
+/// Offsets of the 8 Moore neighbours, row by row.
+pub(crate) const NEIGHBOUR_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1), (0, -1), (1, -1),
+    (-1, 0), (1, 0),
+    (-1, 1), (0, 1), (1, 1),
+];
+
+/// Offsets of the 6 neighbours of a hexagon in an even row, the odd rows being shifted
+/// right by half a cell.
+const HEX_EVEN_ROW_OFFSETS: [(isize, isize); 6] = [(-1, -1), (0, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)];
+const HEX_ODD_ROW_OFFSETS: [(isize, isize); 6] = [(0, -1), (1, -1), (-1, 0), (1, 0), (0, 1), (1, 1)];
+
+/// `value + offset` wrapped into `0..size`.
+pub(crate) fn wrap(value: usize, offset: isize, size: usize) -> usize {
+    (value as isize + offset).rem_euclid(size as isize) as usize
+}
+
+/// `value` reflected across the middle of the board, with the edges at `seam`.
+fn mirror(value: usize, seam: usize, size: usize) -> usize {
+    let relative = wrap(value, -(seam as isize), size);
+    wrap(size - 1 - relative, seam as isize, size)
+}
+
+/// Live cells around `index`, following the board's edges and shape.
+pub fn live_neighbours<G: Grid>(board: &G, index: (usize, usize)) -> u8 {
+    count_live_neighbours(board, index)
+}
+
+fn count_live_neighbours<G: Grid>(board: &G, (ux, uy): (usize, usize)) -> u8 {
+    // boards narrower than 3 cells wrap several offsets onto the same cell,
+    // so collect distinct coordinates first and count each of them once
+    let mut neighbours = [(0, 0); 8];
+    let mut len = 0;
+    let offsets: &[(isize, isize)] = match board.topology() {
+        Topology::Square => &NEIGHBOUR_OFFSETS,
+        Topology::Hex if uy % 2 == 0 => &HEX_EVEN_ROW_OFFSETS,
+        Topology::Hex => &HEX_ODD_ROW_OFFSETS,
+    };
+    for &(dx, dy) in offsets {
+        if let Some(index) = valid_neighbour_index(board, (ux, uy), dx, dy) {
+            if !neighbours[..len].contains(&index) {
+                neighbours[len] = index;
+                len += 1;
             }
         }
     }
-    live_neighbours
+    neighbours[..len].iter()
+        .filter(|&&index| board.in_shape(index) && board.get(index).is_alive())
+        .count() as u8
+}
+
+pub(crate) fn valid_neighbour_index<G: Grid>(board: &G, index: (usize, usize), dx: isize, dy: isize) -> Option<(usize, usize)> {
+    offset_index(board, index, dx, dy).filter(|&neighbour| neighbour != index)
 }
 
-fn valid_neighbour_index(board: &Board, (ux, uy): (usize, usize), x: isize, y: isize) -> Option<(usize, usize)> {
-    if x == ux as isize && y == uy as isize {
-        return None;
+/// Cell `(dx, dy)` away from `(ux, uy)`, following the edges and seam of the board like stepping
+/// does, `None` beyond a bounded edge. Offsets are at most a board's width or height.
+pub(crate) fn offset_index<G: Grid>(board: &G, (ux, uy): (usize, usize), dx: isize, dy: isize) -> Option<(usize, usize)> {
+    let (width, height) = (board.width(), board.height());
+    let (sx, sy) = board.seam();
+    // coordinates relative to the seam, which is where the board ends or is glued together
+    let x = wrap(ux, -(sx as isize), width) as isize + dx;
+    let y = wrap(uy, -(sy as isize), height) as isize + dy;
+    let crosses_x = !(0..width as isize).contains(&x);
+    let crosses_y = !(0..height as isize).contains(&y);
+    let mut x = wrap(x.rem_euclid(width as isize) as usize, sx as isize, width);
+    let mut y = wrap(y.rem_euclid(height as isize) as usize, sy as isize, height);
+    match board.edges() {
+        EdgeBehavior::Bounded if crosses_x || crosses_y => return None,
+        EdgeBehavior::Klein | EdgeBehavior::Projective if crosses_x => y = mirror(y, sy, height),
+        _ => {}
+    }
+    if crosses_y && board.edges() == EdgeBehavior::Projective {
+        x = mirror(x, sx, width);
     }
-    let x = x.rem_euclid(board.width() as isize) as usize;
-    let y = y.rem_euclid(board.height() as isize) as usize;
     Some((x, y))
 }
 
@@ -67,6 +351,17 @@ fn valid_neighbour_index(board: &Board, (ux, uy): (usize, usize), x: isize, y: i
 mod tests {
     use std::ops::Rem;
 
+    use crate::board::{Board, Cell};
+    use crate::grid::{EdgeBehavior, Topology};
+
+    use crate::rng::Rng;
+    use crate::rules::{Rules, StochasticRules};
+
+    use crate::mask::Mask;
+    use crate::pattern::Pattern;
+
+    use super::{classify_motion, count_live_neighbours, detect_cycle, live_neighbours, next_state, next_state_active, next_state_masked, next_state_stochastic, next_state_with, resize, ActiveCells, Cycle, Motion};
+
     #[test]
     fn rem_check() {
         let x = 12;
@@ -81,4 +376,363 @@ mod tests {
         let x = x.rem_euclid(10);
         assert_eq!(x, 10 - 3);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn two_wide_board_counts_each_neighbour_once() {
+        let mut board = Board::new(2, 2);
+        board[(1, 0)] = Cell::Alive;
+        assert_eq!(count_live_neighbours(&board, (0, 0)), 1);
+
+        board[(0, 1)] = Cell::Alive;
+        board[(1, 1)] = Cell::Alive;
+        assert_eq!(count_live_neighbours(&board, (0, 0)), 3);
+    }
+
+    #[test]
+    fn single_column_board_does_not_count_itself() {
+        let mut board = Board::new(1, 4);
+        board[(0, 1)] = Cell::Alive;
+        assert_eq!(count_live_neighbours(&board, (0, 1)), 0);
+        assert_eq!(count_live_neighbours(&board, (0, 0)), 1);
+        assert_eq!(count_live_neighbours(&board, (0, 3)), 0);
+    }
+
+    #[test]
+    fn full_two_by_two_board_is_stable() {
+        let mut board = Board::new(2, 2);
+        for entry in Board::new(2, 2).iter() {
+            board[entry.index()] = Cell::Alive;
+        }
+        assert!(!next_state(&mut board));
+        assert!(board.iter().all(|entry| entry.cell() == Cell::Alive));
+    }
+
+    #[test]
+    fn one_cell_thin_boards_evolve_like_a_ring() {
+        // a 1 x N torus is a ring where every cell has just two neighbours,
+        // so nothing is ever born under B3 and a line shrinks from both ends
+        for (width, height) in [(1, 7), (7, 1)] {
+            let line = (2..5).map(|i| if width == 1 { (0, i) } else { (i, 0) });
+            let mut board = Board::from_live_coords(width, height, line).unwrap();
+            assert!(next_state(&mut board));
+            assert_eq!(board.live_count(), 1);
+            assert!(next_state(&mut board));
+            assert_eq!(board.live_count(), 0);
+            // the last cell fades from died to dead before the ring settles
+            next_state(&mut board);
+            assert!(!next_state(&mut board));
+        }
+        // a full ring survives on S2
+        let mut board = Board::from_live_coords(5, 1, (0..5).map(|x| (x, 0))).unwrap();
+        assert!(!next_state(&mut board));
+        assert_eq!(board.live_count(), 5);
+        // a single cell is nobody's neighbour
+        let mut board = Board::from_live_coords(1, 1, [(0, 0)]).unwrap();
+        assert_eq!(count_live_neighbours(&board, (0, 0)), 0);
+        assert!(next_state(&mut board));
+        assert_eq!(board.live_count(), 0);
+    }
+
+    #[test]
+    fn cells_outside_the_mask_are_frozen_walls() {
+        // a blinker whose left tip is frozen keeps feeding the cells next to it
+        let mut board = Board::from_live_coords(7, 7, [(2, 3), (3, 3), (4, 3)]).unwrap();
+        let mask = Mask::rect(7, 7, (3, 0), (6, 6));
+        assert!(next_state_masked(&mut board, &Rules::conway(), &mask));
+        assert_eq!(board[(2, 3)], Cell::Alive);
+        assert!(board[(3, 2)].is_alive() && board[(3, 3)].is_alive() && board[(3, 4)].is_alive());
+        assert!(!board[(4, 3)].is_alive());
+        for _ in 0..10 {
+            let before = board.clone();
+            next_state_masked(&mut board, &Rules::conway(), &mask);
+            assert!((0..7).all(|y| (0..3).all(|x| board[(x, y)] == before[(x, y)])));
+        }
+
+        let mut full = Board::random(9, 8, 0.4, 5);
+        let mut masked = full.clone();
+        for _ in 0..5 {
+            next_state(&mut full);
+            next_state_masked(&mut masked, &Rules::conway(), &Mask::rect(9, 8, (0, 0), (8, 7)));
+            assert_eq!(full, masked);
+        }
+    }
+
+    #[test]
+    fn bounded_edges_do_not_wrap() {
+        let mut board = Board::new(4, 4);
+        board[(3, 3)] = Cell::Alive;
+        board[(3, 0)] = Cell::Alive;
+        assert_eq!(count_live_neighbours(&board, (0, 0)), 2);
+        board.set_edges(EdgeBehavior::Bounded);
+        assert_eq!(count_live_neighbours(&board, (0, 0)), 0);
+        assert_eq!(count_live_neighbours(&board, (2, 1)), 1);
+    }
+
+    #[test]
+    fn bounded_corners_and_edges_have_fewer_neighbours() {
+        let mut full = Board::new(5, 4);
+        full.fill(Cell::Alive);
+        full.set_edges(EdgeBehavior::Bounded);
+        for corner in [(0, 0), (4, 0), (0, 3), (4, 3)] {
+            assert_eq!(live_neighbours(&full, corner), 3);
+        }
+        for edge in [(2, 0), (0, 1), (4, 2), (3, 3)] {
+            assert_eq!(live_neighbours(&full, edge), 5);
+        }
+        assert_eq!(live_neighbours(&full, (2, 2)), 8);
+
+        // every cell of a block in the corner sees the other three and nothing past the walls
+        let mut block = Board::from_live_coords(6, 6, [(0, 0), (1, 0), (0, 1), (1, 1)]).unwrap();
+        block.set_edges(EdgeBehavior::Bounded);
+        assert!([(0, 0), (1, 0), (0, 1), (1, 1)].iter().all(|&index| live_neighbours(&block, index) == 3));
+        assert_eq!(live_neighbours(&block, (5, 5)), 0);
+        let before = block.clone();
+        assert!(!next_state(&mut block));
+        assert_eq!(block, before);
+    }
+
+    #[test]
+    fn hexagons_have_six_neighbours() {
+        let mut full = Board::new(6, 6);
+        full.fill(Cell::Alive);
+        full.set_topology(Topology::Hex);
+        assert!(full.iter().all(|entry| live_neighbours(&full, entry.index()) == 6));
+
+        // odd rows are shifted right, so their neighbours above and below lean the other way
+        let even = Board::from_live_coords(6, 6, [(3, 1), (3, 3)]).unwrap();
+        let odd = Board::from_live_coords(6, 6, [(1, 2), (1, 4), (3, 2)]).unwrap();
+        for (mut board, index, hex) in [(even, (2, 2), 0), (odd, (2, 3), 1)] {
+            assert_eq!(live_neighbours(&board, index), hex + 2);
+            board.set_topology(Topology::Hex);
+            assert_eq!(live_neighbours(&board, index), hex);
+        }
+
+        // two neighbours give birth to the two cells touching both of them under B2/S34
+        let mut pair = Board::from_live_coords(6, 6, [(2, 2), (3, 2)]).unwrap();
+        pair.set_topology(Topology::Hex);
+        next_state_with(&mut pair, &"B2/S34".parse().unwrap());
+        let live: Vec<_> = pair.iter().filter(|entry| entry.cell().is_alive()).map(|entry| entry.index()).collect();
+        assert_eq!(live, [(2, 1), (2, 3)]);
+    }
+
+    #[test]
+    fn active_stepping_matches_full_stepping() {
+        for edges in [EdgeBehavior::Toroidal, EdgeBehavior::Bounded] {
+            let mut board = Board::random(20, 15, 0.4, 9);
+            board.set_edges(edges);
+            let mut active_board = board.clone();
+            let mut active = ActiveCells::all(&active_board);
+            for _ in 0..30 {
+                let changed = next_state_with(&mut board, &Rules::highlife());
+                assert_eq!(next_state_active(&mut active_board, &Rules::highlife(), &mut active), changed);
+                assert_eq!(active_board, board);
+            }
+        }
+    }
+
+    #[test]
+    fn active_cells_follow_a_glider() {
+        let mut board = Board::from_live_coords(100, 100, [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]).unwrap();
+        let mut active = ActiveCells::all(&board);
+        for _ in 0..8 {
+            next_state_active(&mut board, &Rules::conway(), &mut active);
+        }
+        assert!(active.len() < 50);
+        assert_eq!(board.live_count(), 5);
+    }
+
+    #[test]
+    fn shifted_seam_and_pattern_evolve_identically() {
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let (width, height) = (9, 7);
+        let shift = (4, 5);
+        let shifted = |(x, y): (usize, usize)| ((x + shift.0) % width, (y + shift.1) % height);
+
+        let mut board = Board::from_live_coords(width, height, glider).unwrap();
+        board.set_edges(EdgeBehavior::Bounded);
+        let mut moved = Board::from_live_coords(width, height, glider.map(shifted)).unwrap();
+        moved.set_edges(EdgeBehavior::Bounded);
+        moved.set_seam(shift);
+        for _ in 0..28 {
+            next_state(&mut board);
+            next_state(&mut moved);
+            for entry in board.iter() {
+                assert_eq!(moved[shifted(entry.index())], entry.cell());
+            }
+        }
+        // the glider has crashed into the wall
+        assert_ne!(board.live_count(), 5);
+    }
+
+    #[test]
+    fn seam_splits_bounded_neighbours() {
+        let mut board = Board::from_live_coords(6, 6, [(2, 2), (3, 2)]).unwrap();
+        board.set_edges(EdgeBehavior::Bounded);
+        assert_eq!(count_live_neighbours(&board, (3, 3)), 2);
+        board.set_seam((3, 0));
+        assert_eq!(count_live_neighbours(&board, (3, 3)), 1);
+        assert_eq!(count_live_neighbours(&board, (0, 2)), 0);
+        assert_eq!(count_live_neighbours(&board, (5, 3)), 0);
+    }
+
+    #[test]
+    fn twisted_edges_mirror_neighbours() {
+        let board = |edges, coords: [(usize, usize); 1]| {
+            let mut board = Board::from_live_coords(5, 5, coords).unwrap();
+            board.set_edges(edges);
+            board
+        };
+        // across the sides the row is mirrored on both twisted surfaces
+        assert_eq!(count_live_neighbours(&board(EdgeBehavior::Toroidal, [(4, 3)]), (0, 1)), 0);
+        assert_eq!(count_live_neighbours(&board(EdgeBehavior::Klein, [(4, 3)]), (0, 1)), 1);
+        assert_eq!(count_live_neighbours(&board(EdgeBehavior::Projective, [(4, 3)]), (0, 1)), 1);
+        // across the top and bottom only the projective plane mirrors the column
+        assert_eq!(count_live_neighbours(&board(EdgeBehavior::Klein, [(1, 4)]), (3, 0)), 0);
+        assert_eq!(count_live_neighbours(&board(EdgeBehavior::Klein, [(3, 4)]), (3, 0)), 1);
+        assert_eq!(count_live_neighbours(&board(EdgeBehavior::Projective, [(1, 4)]), (3, 0)), 1);
+    }
+
+    #[test]
+    fn glider_crosses_twisted_edges_with_shifted_seam() {
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let (width, height) = (10, 9);
+        let shift = (3, 6);
+        let shifted = |(x, y): (usize, usize)| ((x + shift.0) % width, (y + shift.1) % height);
+        for edges in [EdgeBehavior::Klein, EdgeBehavior::Projective] {
+            let mut board = Board::from_live_coords(width, height, glider).unwrap();
+            board.set_edges(edges);
+            let mut moved = Board::from_live_coords(width, height, glider.map(shifted)).unwrap();
+            moved.set_edges(edges);
+            moved.set_seam(shift);
+            let mut torus = Board::from_live_coords(width, height, glider).unwrap();
+            for _ in 0..40 {
+                next_state(&mut board);
+                next_state(&mut moved);
+                next_state(&mut torus);
+                for entry in board.iter() {
+                    assert_eq!(moved[shifted(entry.index())], entry.cell());
+                }
+            }
+            assert_ne!(board, torus);
+            // the Klein bottle is flat everywhere, so the glider survives the trip mirrored,
+            // while the corners of the projective plane are not and break it up
+            assert_eq!(board.live_count() == 5, edges == EdgeBehavior::Klein);
+        }
+    }
+
+    #[test]
+    fn resize_keeps_the_shape() {
+        let mut board = Board::from_live_coords(6, 6, [(0, 3), (1, 3), (2, 3)]).unwrap();
+        board.set_shape(Some(Mask::circle(6, 6)));
+        resize(&mut board, 12, 12);
+        let shape = board.shape().unwrap();
+        assert_eq!((shape.width(), shape.height()), (12, 12));
+        assert!(shape.contains((6, 6)) && !shape.contains((0, 0)) && !shape.contains((11, 11)));
+        assert_eq!(board.live_count(), 3);
+        resize(&mut board, 3, 4);
+        assert_eq!(board.shape(), Some(&Mask::circle(6, 6).scaled(3, 4)));
+        assert_eq!(board.live_count(), 3);
+    }
+
+    #[test]
+    fn cells_outside_the_shape_stay_dead() {
+        let blinker = Board::from_live_coords(5, 5, [(1, 2), (2, 2), (3, 2), (0, 0)]).unwrap();
+        let mut shape = Mask::rect(5, 5, (0, 0), (4, 4));
+        shape.set((2, 1), false);
+        shape.set((0, 0), false);
+        let mut board = blinker.clone();
+        board.set_shape(Some(shape));
+        assert!(!board[(0, 0)].is_alive());
+        let mut active = board.clone();
+        next_state_with(&mut board, &Rules::conway());
+        assert!(!board[(2, 1)].is_alive());
+        assert!(board[(2, 2)].is_alive() && board[(2, 3)].is_alive());
+
+        // a cell revived by hand is neither counted nor kept
+        active[(2, 1)] = Cell::Alive;
+        assert_eq!(count_live_neighbours(&active, (1, 1)), 2);
+        next_state_active(&mut active, &Rules::conway(), &mut ActiveCells::all(&blinker));
+        assert_eq!(active, board);
+    }
+
+    #[test]
+    fn stochastic_is_reproducible() {
+        let soup = Board::random(24, 24, 0.4, 11);
+        let mut rules = StochasticRules::from(Rules::conway());
+        rules.survival_prob[2] = 0.8;
+        rules.birth_prob[4] = 0.1;
+        let run = |seed| {
+            let mut board = soup.clone();
+            let mut rng = Rng::new(seed);
+            for _ in 0..10 {
+                next_state_stochastic(&mut board, &rules, &mut rng);
+            }
+            board
+        };
+        assert_eq!(run(5), run(5));
+        assert_ne!(run(5), run(6));
+
+        // certain probabilities behave exactly like the deterministic rules
+        let mut certain = soup.clone();
+        let mut deterministic = soup.clone();
+        for _ in 0..10 {
+            next_state_stochastic(&mut certain, &StochasticRules::from(Rules::conway()), &mut Rng::new(0));
+            next_state_with(&mut deterministic, &Rules::conway());
+        }
+        assert_eq!(certain, deterministic);
+    }
+
+    #[test]
+    fn b0_emulation_matches_the_torus() {
+        // every cell of a torus has 8 neighbours, so the literal rule is the reference
+        for rule in ["B013/S23", "B0125/S8", "B0/S012345678", "B3/S23"] {
+            let rules: Rules = rule.parse().unwrap();
+            let mut literal = Board::random(12, 10, 0.3, 5);
+            let mut emulated = literal.clone();
+            for generation in 0..12 {
+                next_state_with(&mut literal, &rules);
+                next_state_with(&mut emulated, &rules.emulated(generation));
+                assert!(!rules.emulated(generation).is_b0());
+                let inverted = rules.is_inverted(generation + 1);
+                for entry in literal.iter() {
+                    assert_eq!(emulated[entry.index()].is_alive() != inverted, entry.cell().is_alive(), "{} at {}", rule, generation);
+                }
+            }
+        }
+        // the empty background stays dead instead of blinking
+        let mut empty = Board::new(6, 6);
+        let rules: Rules = "B013/S23".parse().unwrap();
+        for generation in 0..4 {
+            next_state_with(&mut empty, &rules.emulated(generation));
+            assert_eq!(empty.live_count(), 0);
+        }
+    }
+
+    #[test]
+    fn detect_cycles() {
+        let mut blinker = Board::from_live_coords(5, 5, [(1, 2), (2, 2), (3, 2)]).unwrap();
+        assert_eq!(detect_cycle(&mut blinker, &Rules::conway(), 8, 100), Some(Cycle { start: 0, period: 2 }));
+        let mut single = Board::from_live_coords(5, 5, [(2, 2)]).unwrap();
+        assert_eq!(detect_cycle(&mut single, &Rules::conway(), 8, 100), Some(Cycle { start: 1, period: 1 }));
+        // a glider takes 32 generations around an 8 x 8 torus
+        let mut glider = Board::new(8, 8);
+        glider.stamp(&Pattern::glider(), (0, 0));
+        assert_eq!(detect_cycle(&mut glider.clone(), &Rules::conway(), 8, 100), None);
+        assert_eq!(detect_cycle(&mut glider, &Rules::conway(), 32, 100), Some(Cycle { start: 0, period: 32 }));
+    }
+
+    #[test]
+    fn spaceships_move() {
+        let conway = Rules::conway();
+        let mut glider = Board::new(8, 8);
+        glider.stamp(&Pattern::glider(), (0, 0));
+        assert_eq!(classify_motion(&mut glider, &conway, 8, 100), Some(Motion::Spaceship { period: 4, dx: 1, dy: 1 }));
+        let mut lwss = Board::new(12, 7);
+        lwss.stamp(&Pattern::lwss(), (6, 1));
+        assert_eq!(classify_motion(&mut lwss, &conway, 8, 100), Some(Motion::Spaceship { period: 4, dx: -2, dy: 0 }));
+        let mut blinker = Board::from_live_coords(5, 5, [(1, 2), (2, 2), (3, 2)]).unwrap();
+        let cycle = Cycle { start: 0, period: 2 };
+        assert_eq!(classify_motion(&mut blinker, &conway, 8, 100), Some(Motion::Stationary(cycle)));
+        assert_eq!(classify_motion(&mut Board::new(3, 3), &conway, 8, 100), Some(Motion::Stationary(Cycle { start: 0, period: 1 })));
+    }
+}
@@ -0,0 +1,127 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Cells that are simulated, everything outside of the mask is frozen in place
+/// but still counts as a neighbour of the cells inside.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Mask {
+    width: usize,
+    height: usize,
+    inner: Vec<bool>,
+}
+
+impl Mask {
+    /// Mask of a `width` x `height` board with every cell frozen.
+    pub fn empty(width: usize, height: usize) -> Self {
+        Mask {
+            width,
+            height,
+            inner: vec![false; width * height],
+        }
+    }
+
+    /// Rectangle between two opposite corners, both inclusive and clipped to the board.
+    pub fn rect(width: usize, height: usize, (x0, y0): (usize, usize), (x1, y1): (usize, usize)) -> Self {
+        let mut mask = Mask::empty(width, height);
+        for y in y0.min(y1)..=y0.max(y1).min(height.saturating_sub(1)) {
+            for x in x0.min(x1)..=x0.max(x1).min(width.saturating_sub(1)) {
+                mask.set((x, y), true);
+            }
+        }
+        mask
+    }
+
+    /// Ellipse inscribed into the board, a circle on square boards.
+    pub fn circle(width: usize, height: usize) -> Self {
+        let mut mask = Mask::empty(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                // cell centers scaled into -1..1 on both axes
+                let dx = (2 * x + 1) as f64 / width as f64 - 1.0;
+                let dy = (2 * y + 1) as f64 / height as f64 - 1.0;
+                mask.set((x, y), dx * dx + dy * dy <= 1.0);
+            }
+        }
+        mask
+    }
+
+    /// The mask cut or extended to `width` x `height` from its top left corner, cells it gains
+    /// are frozen. Keeps a drawn arena over the same cells when the board is resized.
+    pub fn cropped(&self, width: usize, height: usize) -> Self {
+        let mut mask = Mask::empty(width, height);
+        for y in 0..height.min(self.height) {
+            for x in 0..width.min(self.width) {
+                mask.set((x, y), self.contains((x, y)));
+            }
+        }
+        mask
+    }
+
+    /// The mask stretched or squeezed to `width` x `height`, each cell taking the one it falls on
+    /// in the original, so a circle stays inscribed into the board.
+    pub fn scaled(&self, width: usize, height: usize) -> Self {
+        let mut mask = Mask::empty(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                mask.set((x, y), self.contains((x * self.width / width, y * self.height / height)));
+            }
+        }
+        mask
+    }
+
+    pub fn width(&self) -> usize { self.width }
+    pub fn height(&self) -> usize { self.height }
+
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        x < self.width && y < self.height && self.inner[y * self.width + x]
+    }
+
+    pub fn set(&mut self, (x, y): (usize, usize), active: bool) {
+        assert!(x < self.width && y < self.height, "({}, {}) is outside of the mask", x, y);
+        self.inner[y * self.width + x] = active;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Mask;
+
+    #[test]
+    fn rect_corners_in_any_order() {
+        let mask = Mask::rect(6, 5, (4, 3), (1, 1));
+        assert_eq!(mask, Mask::rect(6, 5, (1, 1), (4, 3)));
+        assert!(mask.contains((1, 1)) && mask.contains((4, 3)) && mask.contains((2, 2)));
+        assert!(!mask.contains((0, 1)) && !mask.contains((5, 3)) && !mask.contains((2, 4)));
+        assert!(!mask.contains((10, 10)));
+    }
+
+    #[test]
+    fn circle() {
+        let mask = Mask::circle(9, 9);
+        assert!(mask.contains((4, 4)) && mask.contains((0, 4)) && mask.contains((4, 8)));
+        assert!(!mask.contains((0, 0)) && !mask.contains((8, 8)) && !mask.contains((0, 1)));
+        assert!(mask.contains((1, 1)) && mask.contains((2, 2)));
+    }
+
+    #[test]
+    fn scaled() {
+        let mask = Mask::rect(4, 2, (2, 0), (3, 1)).scaled(8, 6);
+        assert_eq!(mask, Mask::rect(8, 6, (4, 0), (7, 5)));
+        assert_eq!(Mask::circle(9, 9).scaled(9, 9), Mask::circle(9, 9));
+    }
+
+    #[test]
+    fn cropped() {
+        let mask = Mask::rect(6, 5, (1, 1), (4, 3));
+        assert_eq!(mask.cropped(3, 8), Mask::rect(3, 8, (1, 1), (2, 3)));
+        assert_eq!(mask.cropped(6, 5), mask);
+    }
+
+    #[test]
+    fn rect_is_clipped() {
+        let mask = Mask::rect(3, 3, (1, 1), (8, 8));
+        assert!(mask.contains((1, 1)) && mask.contains((2, 2)));
+        assert_eq!((mask.width(), mask.height()), (3, 3));
+    }
+}
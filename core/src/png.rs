@@ -0,0 +1,445 @@
+use crate::image::ImageError;
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+// images with more pixels are refused, a few kilobytes of deflate data can decode to gigabytes
+const MAX_PIXELS: usize = 1 << 26;
+const TRUNCATED: ImageError = ImageError::Format("truncated PNG image");
+
+// base lengths and distances of the deflate codes past the literals, and their extra bits
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+// order the lengths of the code length code are stored in
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+pub(crate) fn is_png(bytes: &[u8]) -> bool {
+    bytes.starts_with(SIGNATURE)
+}
+
+/// Width, height and 8 bit luma of a PNG image of any color type and bit depth, transparent
+/// pixels blended into a white background. Interlaced images are not supported.
+pub(crate) fn decode_png(bytes: &[u8]) -> Result<(usize, usize, Vec<u8>), ImageError> {
+    let mut chunks = bytes.strip_prefix(SIGNATURE).ok_or(ImageError::Format("not a PNG image"))?;
+    let mut header = None;
+    let (mut palette, mut transparency): (&[u8], &[u8]) = (&[], &[]);
+    let mut data = Vec::new();
+    loop {
+        let (length, rest) = chunks.split_first_chunk::<4>().ok_or(TRUNCATED)?;
+        let (kind, rest) = rest.split_first_chunk::<4>().ok_or(TRUNCATED)?;
+        let length = u32::from_be_bytes(*length) as usize;
+        let body = rest.get(..length).ok_or(TRUNCATED)?;
+        // the CRC after each chunk is not checked, damaged pixels show as such
+        chunks = rest.get(length..).and_then(|rest| rest.get(4..)).ok_or(TRUNCATED)?;
+        match kind {
+            b"IHDR" => header = Some(Header::read(body)?),
+            b"PLTE" => palette = body,
+            b"tRNS" => transparency = body,
+            b"IDAT" => data.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+    let header = header.ok_or(ImageError::Format("PNG image without header"))?;
+    let (width, height) = (header.width, header.height);
+    let stride = header.stride().ok_or(ImageError::Format("image is too large"))?;
+    // every row starts with the filter it was encoded with
+    let raw_length = (stride + 1).checked_mul(height).ok_or(ImageError::Format("image is too large"))?;
+    let raw = inflate_zlib(&data, raw_length)?;
+    if raw.len() != raw_length {
+        return Err(TRUNCATED);
+    }
+    let pixels = unfilter(&raw, stride, header.bytes_per_pixel())?;
+
+    let depth = header.depth as usize;
+    let max_sample = (1 << depth) - 1;
+    let to_byte = |value: usize| (value * u8::MAX as usize / max_sample) as u8;
+    let transparent = |channel: usize| {
+        transparency.get(2 * channel..2 * channel + 2).map(|value| u16::from_be_bytes([value[0], value[1]]) as usize)
+    };
+    let mut luma = Vec::with_capacity(width * height);
+    for row in pixels.chunks(stride) {
+        for x in 0..width {
+            let sample = |channel: usize| {
+                let index = x * header.channels + channel;
+                match depth {
+                    8 => row[index] as usize,
+                    16 => u16::from_be_bytes([row[2 * index], row[2 * index + 1]]) as usize,
+                    _ => {
+                        let bit = index * depth;
+                        (row[bit / 8] >> (8 - depth - bit % 8)) as usize & max_sample
+                    }
+                }
+            };
+            let ((r, g, b), alpha) = match header.color_type {
+                0 => {
+                    let gray = to_byte(sample(0));
+                    ((gray, gray, gray), if transparent(0) == Some(sample(0)) { 0 } else { u8::MAX })
+                }
+                2 => {
+                    let opaque = (0..3).any(|channel| transparent(channel) != Some(sample(channel)));
+                    ((to_byte(sample(0)), to_byte(sample(1)), to_byte(sample(2))), if opaque { u8::MAX } else { 0 })
+                }
+                3 => {
+                    let index = sample(0);
+                    let color = palette.get(3 * index..3 * index + 3).ok_or(ImageError::Format("palette index out of range"))?;
+                    ((color[0], color[1], color[2]), transparency.get(index).copied().unwrap_or(u8::MAX))
+                }
+                4 => {
+                    let gray = to_byte(sample(0));
+                    ((gray, gray, gray), to_byte(sample(1)))
+                }
+                _ => ((to_byte(sample(0)), to_byte(sample(1)), to_byte(sample(2))), to_byte(sample(3))),
+            };
+            let gray = (299 * r as usize + 587 * g as usize + 114 * b as usize) / 1000;
+            let alpha = alpha as usize;
+            luma.push(((gray * alpha + u8::MAX as usize * (u8::MAX as usize - alpha)) / u8::MAX as usize) as u8);
+        }
+    }
+    Ok((width, height, luma))
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    depth: u8,
+    color_type: u8,
+    channels: usize,
+}
+
+impl Header {
+    fn read(body: &[u8]) -> Result<Header, ImageError> {
+        let &[w0, w1, w2, w3, h0, h1, h2, h3, depth, color_type, compression, filter, interlace] = body else {
+            return Err(ImageError::Format("invalid PNG header"));
+        };
+        let width = u32::from_be_bytes([w0, w1, w2, w3]) as usize;
+        let height = u32::from_be_bytes([h0, h1, h2, h3]) as usize;
+        let (channels, depths): (usize, &[u8]) = match color_type {
+            0 => (1, &[1, 2, 4, 8, 16]),
+            2 => (3, &[8, 16]),
+            3 => (1, &[1, 2, 4, 8]),
+            4 => (2, &[8, 16]),
+            6 => (4, &[8, 16]),
+            _ => return Err(ImageError::Format("invalid PNG color type")),
+        };
+        if width == 0 || height == 0 || !depths.contains(&depth) || compression != 0 || filter != 0 {
+            return Err(ImageError::Format("invalid PNG header"));
+        }
+        if interlace != 0 {
+            return Err(ImageError::Format("interlaced PNG images are not supported"));
+        }
+        if width.checked_mul(height).is_none_or(|pixels| pixels > MAX_PIXELS) {
+            return Err(ImageError::Format("image is too large"));
+        }
+        Ok(Header { width, height, depth, color_type, channels })
+    }
+
+    /// Bytes of a row of pixels, without its filter byte.
+    fn stride(&self) -> Option<usize> {
+        let bits = self.width.checked_mul(self.channels * self.depth as usize)?;
+        Some(bits.div_ceil(8))
+    }
+
+    /// Distance to the byte of the pixel to the left that filters refer to, at least 1.
+    fn bytes_per_pixel(&self) -> usize {
+        (self.channels * self.depth as usize / 8).max(1)
+    }
+}
+
+/// Undoes the filters rows are encoded with, each relative to the pixels left and above of it.
+fn unfilter(raw: &[u8], stride: usize, bytes_per_pixel: usize) -> Result<Vec<u8>, ImageError> {
+    let mut pixels = vec![0u8; raw.len() / (stride + 1) * stride];
+    for (y, line) in raw.chunks(stride + 1).enumerate() {
+        let (&filter, line) = line.split_first().ok_or(TRUNCATED)?;
+        if filter > 4 {
+            return Err(ImageError::Format("invalid PNG filter"));
+        }
+        let row = y * stride;
+        for x in 0..stride {
+            let left = if x >= bytes_per_pixel { pixels[row + x - bytes_per_pixel] } else { 0 };
+            let up = if y > 0 { pixels[row - stride + x] } else { 0 };
+            let up_left = if x >= bytes_per_pixel && y > 0 { pixels[row - stride + x - bytes_per_pixel] } else { 0 };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                _ => paeth(left, up, up_left),
+            };
+            pixels[row + x] = line[x].wrapping_add(predicted);
+        }
+    }
+    Ok(pixels)
+}
+
+// whichever neighbour is closest to `left + up - up_left`, preferring them in this order
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let distance = |value: u8| (estimate - value as i16).abs();
+    if distance(left) <= distance(up) && distance(left) <= distance(up_left) {
+        left
+    } else if distance(up) <= distance(up_left) {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// Decompresses a zlib stream, failing once it holds more than `limit` bytes.
+/// The Adler-32 checksum at its end is not checked.
+fn inflate_zlib(bytes: &[u8], limit: usize) -> Result<Vec<u8>, ImageError> {
+    let &[method, flags, ..] = bytes else {
+        return Err(TRUNCATED);
+    };
+    if method & 0x0f != 8 || !(method as u16 * 256 + flags as u16).is_multiple_of(31) || flags & 0x20 != 0 {
+        return Err(ImageError::Format("invalid zlib header"));
+    }
+    let mut bits = Bits { bytes: &bytes[2..], position: 0 };
+    let mut out = Vec::new();
+    loop {
+        let last = bits.read(1)? == 1;
+        match bits.read(2)? {
+            0 => {
+                // stored blocks start at a byte boundary with their length and its complement
+                let start = bits.position.div_ceil(8);
+                let (&[l0, l1, n0, n1], _) = bits.bytes[start.min(bits.bytes.len())..].split_first_chunk::<4>().ok_or(TRUNCATED)?;
+                let length = u16::from_le_bytes([l0, l1]);
+                if length != !u16::from_le_bytes([n0, n1]) {
+                    return Err(ImageError::Format("invalid stored deflate block"));
+                }
+                let stored = bits.bytes.get(start + 4..start + 4 + length as usize).ok_or(TRUNCATED)?;
+                if out.len() + stored.len() > limit {
+                    return Err(ImageError::Format("more pixel data than the image holds"));
+                }
+                out.extend_from_slice(stored);
+                bits.position = (start + 4 + length as usize) * 8;
+            }
+            1 => {
+                let mut lengths = [0u8; 288 + 30];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..288].fill(8);
+                lengths[288..].fill(5);
+                inflate_block(&mut bits, &mut out, &Huffman::new(&lengths[..288])?, &Huffman::new(&lengths[288..])?, limit)?;
+            }
+            2 => {
+                let literals = bits.read(5)? as usize + 257;
+                let distances = bits.read(5)? as usize + 1;
+                let code_lengths = bits.read(4)? as usize + 4;
+                let mut lengths = [0u8; 19];
+                for &symbol in &CODE_LENGTH_ORDER[..code_lengths] {
+                    lengths[symbol] = bits.read(3)? as u8;
+                }
+                let code_length_code = Huffman::new(&lengths)?;
+                let mut lengths = Vec::with_capacity(literals + distances);
+                while lengths.len() < literals + distances {
+                    let (length, repeat) = match code_length_code.decode(&mut bits)? {
+                        symbol @ 0..=15 => (symbol as u8, 1),
+                        16 => (*lengths.last().ok_or(ImageError::Format("invalid deflate code lengths"))?, 3 + bits.read(2)?),
+                        17 => (0, 3 + bits.read(3)?),
+                        _ => (0, 11 + bits.read(7)?),
+                    };
+                    lengths.extend(std::iter::repeat_n(length, repeat as usize));
+                }
+                if lengths.len() > literals + distances || lengths[256] == 0 {
+                    return Err(ImageError::Format("invalid deflate code lengths"));
+                }
+                let (literal_lengths, distance_lengths) = lengths.split_at(literals);
+                inflate_block(&mut bits, &mut out, &Huffman::new(literal_lengths)?, &Huffman::new(distance_lengths)?, limit)?;
+            }
+            _ => return Err(ImageError::Format("invalid deflate block")),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+fn inflate_block(bits: &mut Bits, out: &mut Vec<u8>, literals: &Huffman, distances: &Huffman, limit: usize) -> Result<(), ImageError> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let index = symbol - 257;
+                let base = *LENGTH_BASE.get(index).ok_or(ImageError::Format("invalid deflate length"))? as usize;
+                let length = base + bits.read(LENGTH_EXTRA[index])? as usize;
+                let index = distances.decode(bits)? as usize;
+                let base = *DISTANCE_BASE.get(index).ok_or(ImageError::Format("invalid deflate distance"))? as usize;
+                let distance = base + bits.read(DISTANCE_EXTRA[index])? as usize;
+                let start = out.len().checked_sub(distance).ok_or(ImageError::Format("invalid deflate distance"))?;
+                // the copy may overlap what it writes, repeating the last `distance` bytes
+                for index in start..start + length {
+                    out.push(out[index]);
+                }
+            }
+        }
+        if out.len() > limit {
+            return Err(ImageError::Format("more pixel data than the image holds"));
+        }
+    }
+}
+
+struct Bits<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Bits<'_> {
+    /// The next `count` bits, least significant first as deflate packs them.
+    fn read(&mut self, count: u8) -> Result<u32, ImageError> {
+        let mut value = 0;
+        for bit in 0..count {
+            let byte = self.bytes.get(self.position / 8).ok_or(TRUNCATED)?;
+            value |= ((byte >> (self.position % 8)) as u32 & 1) << bit;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+}
+
+/// Canonical Huffman code given by the code length of each symbol, 0 for unused ones.
+struct Huffman {
+    // number of codes of each length
+    counts: [u16; 16],
+    // symbols ordered by code length, then by value
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Huffman, ImageError> {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+        // codes running out before every symbol got one cannot be decoded, incomplete codes can
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = 2 * left - count as i32;
+            if left < 0 {
+                return Err(ImageError::Format("invalid deflate code lengths"));
+            }
+        }
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16).filter(|&symbol| lengths[symbol as usize] != 0).collect();
+        symbols.sort_by_key(|&symbol| lengths[symbol as usize]);
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16, ImageError> {
+        // codes of each length follow the last one of the length before, shifted left by a bit
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0usize);
+        for &count in &self.counts[1..] {
+            code |= bits.read(1)? as i32;
+            if code - first < count as i32 {
+                return Ok(self.symbols[index + (code - first) as usize]);
+            }
+            index += count as usize;
+            first = (first + count as i32) << 1;
+            code <<= 1;
+        }
+        Err(ImageError::Format("invalid deflate code"))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::image::ImageError;
+
+    use super::{decode_png, inflate_zlib, SIGNATURE};
+
+    // the CRCs are left zero, as they are not checked
+    fn png(header: [u8; 13], chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let mut png = SIGNATURE.to_vec();
+        for &(kind, body) in [(b"IHDR", &header[..])].iter().chain(chunks).chain(&[(b"IEND", &[][..])]) {
+            png.extend((body.len() as u32).to_be_bytes());
+            png.extend(kind);
+            png.extend(body);
+            png.extend([0; 4]);
+        }
+        png
+    }
+
+    fn header(width: u32, height: u32, depth: u8, color_type: u8) -> [u8; 13] {
+        let mut header = [0; 13];
+        header[..4].copy_from_slice(&width.to_be_bytes());
+        header[4..8].copy_from_slice(&height.to_be_bytes());
+        header[8..10].copy_from_slice(&[depth, color_type]);
+        header
+    }
+
+    // zlib stream of a single stored block, without a checksum
+    fn stored(raw: &[u8]) -> Vec<u8> {
+        let length = raw.len() as u16;
+        let mut zlib = vec![0x78, 0x01, 0x01];
+        zlib.extend(length.to_le_bytes());
+        zlib.extend((!length).to_le_bytes());
+        zlib.extend(raw);
+        zlib
+    }
+
+    #[test]
+    fn grayscale() {
+        // rows [0, 255, 0] and [255, 0, 255], the second one filtered by the pixel to its left
+        let idat = [120, 218, 99, 96, 248, 207, 192, 248, 159, 241, 63, 0, 10, 4, 3, 0];
+        let png = png(header(3, 2, 8, 0), &[(b"IDAT", &idat[..5]), (b"tEXt", b"Comment\0gol"), (b"IDAT", &idat[5..])]);
+        assert_eq!(decode_png(&png).unwrap(), (3, 2, vec![0, 255, 0, 255, 0, 255]));
+    }
+
+    #[test]
+    fn palette_with_transparency() {
+        // 1 bit indices into black, white and a transparent red, rows padded to a byte
+        let raw = [0, 0b1010_0000, 0, 0b0100_0000];
+        let chunks = [(b"PLTE", &[0, 0, 0, 255, 0, 0][..]), (b"tRNS", &[255, 0][..]), (b"IDAT", &stored(&raw)[..])];
+        assert_eq!(decode_png(&png(header(3, 2, 1, 3), &chunks)).unwrap(), (3, 2, vec![255, 0, 255, 0, 255, 0]));
+        // an index past the palette
+        let chunks = [(b"PLTE", &[0, 0, 0][..]), (b"IDAT", &stored(&raw)[..])];
+        assert!(matches!(decode_png(&png(header(3, 2, 1, 3), &chunks)), Err(ImageError::Format(_))));
+    }
+
+    #[test]
+    fn color_with_alpha() {
+        // a red pixel above a half transparent black one, with the up and paeth filters
+        let raw = [2, 255, 0, 0, 255, 4, 1, 0, 0, 129];
+        let png = png(header(1, 2, 8, 6), &[(b"IDAT", &stored(&raw))]);
+        assert_eq!(decode_png(&png).unwrap(), (1, 2, vec![76, 127]));
+    }
+
+    #[test]
+    fn dynamic_huffman_codes() {
+        let text = b"Any live cell with two or three live neighbours survives. Any dead cell with three live \
+            neighbours becomes a live cell. All other live cells die in the next generation. Similarly, all \
+            other dead cells stay dead.";
+        let zlib = [
+            120, 218, 109, 206, 209, 13, 131, 48, 12, 4, 208, 85, 110, 128, 42, 59, 48, 67, 39, 48,
+            196, 34, 150, 66, 44, 57, 6, 202, 246, 117, 133, 4, 124, 244, 247, 172, 231, 187, 161, 29,
+            168, 178, 49, 38, 174, 21, 187, 120, 129, 239, 10, 53, 120, 49, 230, 243, 214, 88, 230, 50,
+            234, 106, 29, 125, 181, 45, 162, 158, 48, 132, 204, 76, 249, 41, 255, 146, 145, 39, 93, 184,
+            131, 238, 162, 208, 97, 212, 11, 219, 29, 118, 100, 97, 72, 139, 55, 63, 255, 113, 204, 220,
+            216, 200, 69, 91, 194, 91, 22, 169, 100, 245, 120, 129, 46, 123, 245, 199, 48, 167, 115, 79,
+            250, 2, 188, 161, 76, 44,
+        ];
+        assert_eq!(inflate_zlib(&zlib, text.len()).unwrap(), text);
+        assert!(inflate_zlib(&zlib, text.len() - 1).is_err());
+        assert!(inflate_zlib(&zlib[..60], text.len()).is_err());
+    }
+
+    #[test]
+    fn invalid_images() {
+        let idat = stored(&[0, 0]);
+        let valid = png(header(1, 1, 8, 0), &[(b"IDAT", &idat)]);
+        assert!(decode_png(&valid).is_ok());
+        assert!(decode_png(&valid[..valid.len() - 12]).is_err());
+        let mut interlaced = header(1, 1, 8, 0);
+        interlaced[12] = 1;
+        for header in [interlaced, header(1, 1, 3, 0), header(1, 1, 16, 3), header(0, 1, 8, 0), header(1 << 16, 1 << 16, 8, 0)] {
+            assert!(matches!(decode_png(&png(header, &[(b"IDAT", &idat)])), Err(ImageError::Format(_))));
+        }
+        // more rows than the header has room for
+        assert!(decode_png(&png(header(1, 1, 8, 0), &[(b"IDAT", &stored(&[0, 0, 0, 0]))])).is_err());
+        assert!(decode_png(&png(header(1, 1, 8, 0), &[(b"IDAT", &stored(&[5, 0]))])).is_err());
+    }
+}
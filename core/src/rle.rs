@@ -0,0 +1,284 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::board::Board;
+use crate::pattern::{Pattern, PatternError};
+use crate::rules::Rules;
+
+const LINE_LENGTH: usize = 70;
+// limits of what RLE may decode to, so that a few bytes of input cannot ask for gigabytes:
+// the largest coordinate a run may reach, and the most cells the box around the live ones may
+// hold, as boards and LifeHistory layers are allocated for all of them
+pub(crate) const MAX_EXTENT: usize = 1 << 20;
+pub(crate) const MAX_AREA: usize = 1 << 24;
+
+/// The `#` lines of an RLE pattern worth keeping when it is saved again.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PatternMeta {
+    /// `#N`
+    pub name: Option<String>,
+    /// `#O`, who found the pattern and when.
+    pub author: Option<String>,
+    /// `#C` and `#c`, in their order.
+    pub comments: Vec<String>,
+    /// `#r` or the `rule` of the header, unset for rules `Rules` cannot express such as LifeHistory.
+    pub rules: Option<Rules>,
+}
+
+/// Run Length Encoded pattern as used by Golly and LifeWiki.
+pub fn to_rle(board: &Board, rules: &Rules) -> String {
+    to_rle_with_meta(board, rules, &PatternMeta::default())
+}
+
+/// Like `to_rle`, with the name, author and comments of `meta` written ahead of the header.
+/// The header names `rules`, not those of `meta`.
+pub fn to_rle_with_meta(board: &Board, rules: &Rules, meta: &PatternMeta) -> String {
+    let mut lines = String::new();
+    let tagged = [("#N", &meta.name), ("#O", &meta.author)];
+    for (tag, text) in tagged.into_iter().filter_map(|(tag, text)| Some((tag, text.as_ref()?))) {
+        writeln!(lines, "{} {}", tag, text).expect("writing to a string cannot fail");
+    }
+    for comment in &meta.comments {
+        writeln!(lines, "#C {}", comment).expect("writing to a string cannot fail");
+    }
+    let mut writer = RleWriter::default();
+    let mut pending_rows = 0;
+    for y in 0..board.height() {
+        let row_end = (0..board.width())
+            .rev()
+            .find(|&x| board[(x, y)].is_alive())
+            .map(|x| x + 1);
+        let Some(row_end) = row_end else {
+            pending_rows += 1;
+            continue;
+        };
+        if writer.written {
+            writer.push(pending_rows + 1, '$');
+        } else if pending_rows > 0 {
+            writer.push(pending_rows, '$');
+        }
+        pending_rows = 0;
+        let mut x = 0;
+        while x < row_end {
+            let alive = board[(x, y)].is_alive();
+            let run = (x..row_end).take_while(|&x| board[(x, y)].is_alive() == alive).count();
+            writer.push(run, if alive { 'o' } else { 'b' });
+            x += run;
+        }
+    }
+    writer.body.push('!');
+    format!("{}x = {}, y = {}, rule = {}\n{}\n", lines, board.width(), board.height(), rules, writer.body)
+}
+
+/// Reads the `#` lines and header rule of an RLE pattern, its cells are left to `from_rle`.
+pub fn read_meta(s: &str) -> PatternMeta {
+    let mut meta = PatternMeta::default();
+    let lines = s.lines()
+        .map(str::trim)
+        .take_while(|line| line.starts_with('#') || line.starts_with('x') || line.is_empty());
+    for line in lines {
+        let rule = if line.starts_with('x') {
+            line.split(',')
+                .filter_map(|item| item.split_once('='))
+                .find(|(key, _)| key.trim() == "rule")
+                .map(|(_, value)| value.trim())
+        } else {
+            let (tag, text) = if line.is_char_boundary(2) { line.split_at(2) } else { (line, "") };
+            let text = text.trim().to_string();
+            match tag {
+                "#N" => meta.name = Some(text),
+                "#O" => meta.author = Some(text),
+                "#C" | "#c" => meta.comments.push(text),
+                "#r" => meta.rules = text.parse().ok(),
+                _ => {}
+            }
+            None
+        };
+        if let Some(rule) = rule {
+            meta.rules = rule.parse().ok();
+        }
+    }
+    meta
+}
+
+/// Whether `s` looks like RLE, by its header, `$` row separators or `!` terminator.
+pub fn is_rle(s: &str) -> bool {
+    let s = s.trim();
+    s.starts_with('x') || s.contains('$') || s.ends_with('!')
+}
+
+/// Reads the live cells of an RLE pattern, skipping `#` comments and the `x = ...` header.
+pub fn from_rle(s: &str) -> Result<Pattern, PatternError> {
+    let mut cells = Vec::new();
+    let mut right = 0;
+    read_runs(s, |tag, (x, y), count| match tag {
+        'b' | '.' => Ok(()),
+        'o' | 'A'..='Z' => {
+            check_area(&mut right, (x, y), count)?;
+            cells.extend((x..x + count).map(|x| (x, y)));
+            Ok(())
+        }
+        _ => Err(PatternError::Unexpected(tag)),
+    })?;
+    Ok(Pattern::from_cells(cells))
+}
+
+/// Widens `right` to the end of the live run at `(x, y)`, unless the box from the origin to it
+/// holds more than `MAX_AREA` cells.
+pub(crate) fn check_area(right: &mut usize, (x, y): (usize, usize), count: usize) -> Result<(), PatternError> {
+    *right = (*right).max(x + count);
+    match right.checked_mul(y + 1) {
+        Some(area) if area <= MAX_AREA => Ok(()),
+        _ => Err(PatternError::TooLarge),
+    }
+}
+
+/// Calls `run` with every tag other than `$` and `!`, the position its run starts at and its length,
+/// stopping at the first error. Runs reaching beyond `MAX_EXTENT` are rejected before that.
+pub(crate) fn read_runs(s: &str, mut run: impl FnMut(char, (usize, usize), usize) -> Result<(), PatternError>) -> Result<(), PatternError> {
+    let (mut x, mut y) = (0usize, 0usize);
+    let mut length: Option<usize> = None;
+    let body = s.lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#') && !line.starts_with('x'));
+    for line in body {
+        for c in line.chars().filter(|c| !c.is_whitespace()) {
+            if let Some(digit) = c.to_digit(10) {
+                let digits = length.unwrap_or(0).checked_mul(10).and_then(|length| length.checked_add(digit as usize));
+                length = Some(digits.ok_or(PatternError::Unexpected(c))?);
+                continue;
+            }
+            let count = length.take().unwrap_or(1);
+            let end = match c {
+                '$' => y.checked_add(count),
+                _ => x.checked_add(count),
+            };
+            let end = end.filter(|&end| end <= MAX_EXTENT).ok_or(PatternError::TooLarge)?;
+            match c {
+                '$' => {
+                    x = 0;
+                    y = end;
+                }
+                '!' => return Ok(()),
+                _ => {
+                    run(c, (x, y), count)?;
+                    x = end;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct RleWriter {
+    body: String,
+    line_length: usize,
+    written: bool,
+}
+
+impl RleWriter {
+    fn push(&mut self, run: usize, tag: char) {
+        let item = if run == 1 { format!("{}", tag) } else { format!("{}{}", run, tag) };
+        if self.line_length + item.len() > LINE_LENGTH {
+            self.body.push('\n');
+            self.line_length = 0;
+        }
+        self.line_length += item.len();
+        self.body.push_str(&item);
+        self.written = true;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{Board, Cell};
+    use crate::pattern::{Pattern, PatternError};
+    use crate::rules::Rules;
+
+    use super::{from_rle, read_meta, to_rle, to_rle_with_meta, PatternMeta};
+
+    #[test]
+    fn glider_to_rle() {
+        let mut board = Board::new(5, 5);
+        for index in [(1, 1), (2, 2), (0, 3), (1, 3), (2, 3)] {
+            board[index] = Cell::Alive;
+        }
+        assert_eq!(to_rle(&board, &Rules::conway()), "x = 5, y = 5, rule = B3/S23\n$bo$2bo$3o!\n");
+    }
+
+    #[test]
+    fn empty_rows_are_collapsed() {
+        let mut board = Board::new(4, 6);
+        board[(0, 0)] = Cell::Born;
+        board[(3, 4)] = Cell::Alive;
+        assert_eq!(to_rle(&board, &Rules::highlife()), "x = 4, y = 6, rule = B36/S23\no4$3bo!\n");
+    }
+
+    #[test]
+    fn long_lines_are_wrapped() {
+        let mut board = Board::new(200, 1);
+        for x in (0..200).step_by(2) {
+            board[(x, 0)] = Cell::Alive;
+        }
+        let rle = to_rle(&board, &Rules::conway());
+        assert!(rle.lines().all(|line| line.len() <= 70));
+        assert_eq!(rle.lines().skip(1).collect::<String>().matches('o').count(), 100);
+    }
+
+    #[test]
+    fn parse_rle() {
+        let rle = "#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2b\no$3o!";
+        assert_eq!(from_rle(rle), Ok(Pattern::glider()));
+        assert_eq!(from_rle("2o2$12bo!").unwrap().cells(), &[(0, 0), (1, 0), (12, 2)]);
+        assert_eq!(from_rle("3q!"), Err(PatternError::Unexpected('q')));
+    }
+
+    #[test]
+    fn huge_runs_are_rejected() {
+        assert_eq!(from_rle("18446744073709551615b2o!"), Err(PatternError::TooLarge));
+        assert_eq!(from_rle("4000000000o!"), Err(PatternError::TooLarge));
+        assert_eq!(from_rle("o18446744073709551615$o!"), Err(PatternError::TooLarge));
+        // runs within the extent still span too large a box
+        assert_eq!(from_rle(&("1000000o$".repeat(20) + "!")), Err(PatternError::TooLarge));
+        assert_eq!(from_rle("o5000$5000bo!"), Err(PatternError::TooLarge));
+        assert_eq!(from_rle("1000000bo!").unwrap().cells(), &[(1_000_000, 0)]);
+    }
+
+    #[test]
+    fn rle_round_trip() {
+        let board = Board::random(23, 9, 0.4, 7);
+        let rle = to_rle(&board, &Rules::conway());
+        let pattern = from_rle(&rle).unwrap();
+        assert_eq!(pattern.cells().len(), board.live_count());
+        let mut copy = Board::new(23, 9);
+        copy.stamp(&pattern, (0, 0));
+        assert!(copy.iter().all(|entry| entry.cell().is_alive() == board[entry.index()].is_alive()));
+    }
+
+    #[test]
+    fn metadata_round_trip() {
+        let rle = "#N Glider\n#O Richard K. Guy, 1969\n#C The smallest spaceship.\n#C www.conwaylife.com\nx = 3, y = 3, rule = B36/S23\nbo$2bo$3o!";
+        let meta = read_meta(rle);
+        assert_eq!(meta, PatternMeta {
+            name: Some("Glider".to_string()),
+            author: Some("Richard K. Guy, 1969".to_string()),
+            comments: vec!["The smallest spaceship.".to_string(), "www.conwaylife.com".to_string()],
+            rules: Some(Rules::highlife()),
+        });
+        let mut board = Board::new(3, 3);
+        board.stamp(&from_rle(rle).unwrap(), (0, 0));
+        let saved = to_rle_with_meta(&board, &Rules::highlife(), &meta);
+        assert_eq!(saved, format!("{}\n", rle));
+        assert_eq!(read_meta(&saved), meta);
+
+        assert_eq!(read_meta("#r B2/S\n2o!").rules, Some(Rules::new(&[2], &[])));
+        assert_eq!(read_meta("x = 1, y = 1, rule = LifeHistory\nA!").rules, None);
+        assert_eq!(read_meta("#N a\nx = 1, y = 1, rule = B9\no!").rules, None);
+        assert_eq!(read_meta("o!"), PatternMeta::default());
+        assert_eq!(read_meta("#\n#é\no!"), PatternMeta::default());
+    }
+}
@@ -0,0 +1,271 @@
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+use crate::board::Cell;
+use crate::rng::Rng;
+
+/// Outer-totalistic rule in B/S notation, e.g. `B3/S23` for Conway's Life.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "alloc::string::String", try_from = "alloc::string::String"))]
+pub struct Rules {
+    birth: [bool; 9],
+    survival: [bool; 9],
+}
+
+impl Rules {
+    pub fn new(birth: &[u8], survival: &[u8]) -> Self {
+        let mut rules = Rules {
+            birth: [false; 9],
+            survival: [false; 9],
+        };
+        for &count in birth {
+            rules.birth[count as usize] = true;
+        }
+        for &count in survival {
+            rules.survival[count as usize] = true;
+        }
+        rules
+    }
+
+    pub fn conway() -> Self {
+        Rules::new(&[3], &[2, 3])
+    }
+
+    pub fn highlife() -> Self {
+        Rules::new(&[3, 6], &[2, 3])
+    }
+
+    pub fn day_and_night() -> Self {
+        Rules::new(&[3, 6, 7, 8], &[3, 4, 6, 7, 8])
+    }
+
+    pub fn is_birth(&self, live_neighbours: u8) -> bool {
+        self.birth[live_neighbours as usize]
+    }
+
+    pub fn is_survival(&self, live_neighbours: u8) -> bool {
+        self.survival[live_neighbours as usize]
+    }
+
+    /// Whether cells are born without live neighbours, which turns the whole empty
+    /// background alive after one generation.
+    pub fn is_b0(&self) -> bool {
+        self.birth[0]
+    }
+
+    /// Rule stepping `generation` to the next one while the background is kept dead, the
+    /// standard emulation of `B0` rules. Under `B0` without `S8` the background blinks, so odd
+    /// generations are stored inverted and the rule alternates between two complements.
+    /// With `S8` the background stays alive, so every generation after the first is stored
+    /// inverted. Other rules are returned as they are. `generation` counts from a board
+    /// holding the pattern itself, see `is_inverted`.
+    pub fn emulated(&self, generation: u64) -> Rules {
+        if !self.is_b0() {
+            return *self;
+        }
+        let flipped = |counts: &[bool; 9], keep: bool| core::array::from_fn(|n| counts[8 - n] == keep);
+        match (self.is_inverted(generation), self.survival[8]) {
+            // the pattern turns into the inverse of what it becomes
+            (false, _) => Rules {
+                birth: self.birth.map(|born| !born),
+                survival: self.survival.map(|survives| !survives),
+            },
+            // the inverse turns back into the pattern, where cells have 8 - n live neighbours
+            (true, false) => Rules {
+                birth: flipped(&self.survival, true),
+                survival: flipped(&self.birth, true),
+            },
+            // the inverse stays inverted
+            (true, true) => Rules {
+                birth: flipped(&self.survival, false),
+                survival: flipped(&self.birth, false),
+            },
+        }
+    }
+
+    /// Whether a board stepped with `emulated` holds the inverse of `generation`.
+    pub fn is_inverted(&self, generation: u64) -> bool {
+        self.is_b0() && generation > 0 && (self.survival[8] || generation % 2 == 1)
+    }
+
+    pub fn next_cell(&self, cell: Cell, live_neighbours: u8) -> Cell {
+        match cell {
+            Cell::Dead | Cell::Died if self.is_birth(live_neighbours) => Cell::Born,
+            Cell::Alive | Cell::Born if !self.is_survival(live_neighbours) => Cell::Died,
+            // move state further
+            Cell::Born => Cell::Alive,
+            Cell::Died => Cell::Dead,
+            _ => cell,
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules::conway()
+    }
+}
+
+impl Display for Rules {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "B")?;
+        for count in (0..9).filter(|&count| self.birth[count]) {
+            write!(f, "{}", count)?;
+        }
+        write!(f, "/S")?;
+        for count in (0..9).filter(|&count| self.survival[count]) {
+            write!(f, "{}", count)?;
+        }
+        Ok(())
+    }
+}
+
+/// Noisy variant of `Rules`: a dead cell with `n` live neighbours is born with probability
+/// `birth_prob[n]` and a live one survives with probability `survival_prob[n]`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StochasticRules {
+    pub birth_prob: [f64; 9],
+    pub survival_prob: [f64; 9],
+}
+
+impl StochasticRules {
+    pub fn new(birth_prob: [f64; 9], survival_prob: [f64; 9]) -> Self {
+        let valid = |p: &f64| (0.0..=1.0).contains(p);
+        assert!(birth_prob.iter().chain(&survival_prob).all(valid), "probabilities must be within 0 and 1");
+        StochasticRules { birth_prob, survival_prob }
+    }
+
+    /// Same as `Rules::next_cell` but drawing from `rng` for probabilities strictly between 0 and 1.
+    pub fn next_cell(&self, cell: Cell, live_neighbours: u8, rng: &mut Rng) -> Cell {
+        let count = live_neighbours as usize;
+        match cell {
+            Cell::Dead | Cell::Died if chance(self.birth_prob[count], rng) => Cell::Born,
+            Cell::Alive | Cell::Born if !chance(self.survival_prob[count], rng) => Cell::Died,
+            Cell::Born => Cell::Alive,
+            Cell::Died => Cell::Dead,
+            _ => cell,
+        }
+    }
+}
+
+impl From<Rules> for StochasticRules {
+    fn from(rules: Rules) -> Self {
+        let probability = |happens: bool| if happens { 1.0 } else { 0.0 };
+        StochasticRules {
+            birth_prob: rules.birth.map(probability),
+            survival_prob: rules.survival.map(probability),
+        }
+    }
+}
+
+fn chance(probability: f64, rng: &mut Rng) -> bool {
+    probability >= 1.0 || (probability > 0.0 && rng.next_f64() < probability)
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RulesError {
+    Format,
+    Digit(char),
+}
+
+impl Display for RulesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RulesError::Format => write!(f, "expected rule in B/S notation, e.g. B3/S23"),
+            RulesError::Digit(c) => write!(f, "invalid neighbour count `{}`", c),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RulesError {}
+
+impl FromStr for Rules {
+    type Err = RulesError;
+
+    /// Accepts `B3/S23` in any case and order, and the legacy `23/3` survival/birth form.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (first, second) = s.trim().split_once('/').ok_or(RulesError::Format)?;
+        let (birth, survival) = match (first.chars().next(), second.chars().next()) {
+            (Some('B' | 'b'), Some('S' | 's')) => (&first[1..], &second[1..]),
+            (Some('S' | 's'), Some('B' | 'b')) => (&second[1..], &first[1..]),
+            _ => (second, first),
+        };
+        let mut rules = Rules::new(&[], &[]);
+        for (digits, counts) in [(birth, &mut rules.birth), (survival, &mut rules.survival)] {
+            for c in digits.chars() {
+                match c.to_digit(10) {
+                    Some(count) if count < 9 => counts[count as usize] = true,
+                    _ => return Err(RulesError::Digit(c)),
+                }
+            }
+        }
+        Ok(rules)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Rules> for alloc::string::String {
+    fn from(rules: Rules) -> Self {
+        alloc::string::ToString::to_string(&rules)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<alloc::string::String> for Rules {
+    type Error = RulesError;
+
+    fn try_from(s: alloc::string::String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Cell;
+    use crate::rng::Rng;
+
+    use super::{Rules, RulesError, StochasticRules};
+
+    #[test]
+    fn parse_rules() {
+        assert_eq!("B3/S23".parse(), Ok(Rules::conway()));
+        assert_eq!("b36/s23".parse(), Ok(Rules::highlife()));
+        assert_eq!("S23/B3".parse(), Ok(Rules::conway()));
+        assert_eq!("23/3".parse(), Ok(Rules::conway()));
+        assert_eq!("B/S".parse(), Ok(Rules::new(&[], &[])));
+        assert_eq!("B3S23".parse::<Rules>(), Err(RulesError::Format));
+        assert_eq!("B9/S23".parse::<Rules>(), Err(RulesError::Digit('9')));
+    }
+
+    #[test]
+    fn display_round_trip() {
+        assert_eq!(Rules::highlife().to_string(), "B36/S23");
+        let rules: Rules = "B0125/S8".parse().unwrap();
+        assert_eq!(rules.to_string().parse(), Ok(rules));
+    }
+
+    #[test]
+    fn next_cell() {
+        let rules = Rules::highlife();
+        assert_eq!(rules.next_cell(Cell::Dead, 6), Cell::Born);
+        assert_eq!(rules.next_cell(Cell::Died, 2), Cell::Dead);
+        assert_eq!(rules.next_cell(Cell::Born, 2), Cell::Alive);
+        assert_eq!(rules.next_cell(Cell::Alive, 6), Cell::Died);
+    }
+
+    #[test]
+    fn stochastic_next_cell() {
+        let mut rng = Rng::new(3);
+        let certain = StochasticRules::from(Rules::highlife());
+        for (cell, count) in [(Cell::Dead, 6), (Cell::Died, 2), (Cell::Born, 2), (Cell::Alive, 6)] {
+            assert_eq!(certain.next_cell(cell, count, &mut rng), Rules::highlife().next_cell(cell, count));
+        }
+        let mut coin = StochasticRules::from(Rules::conway());
+        coin.birth_prob[3] = 0.5;
+        let births = (0..1000).filter(|_| coin.next_cell(Cell::Dead, 3, &mut rng) == Cell::Born).count();
+        assert!((400..600).contains(&births), "{} births", births);
+    }
+}
@@ -0,0 +1,97 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::board::{Board, Cell};
+use crate::grid::{EdgeBehavior, Topology};
+
+/// Serialized form of a `Board`: dimensions plus the row-major cells run length
+/// encoded with their display glyphs, e.g. `3X2O` for three dead then two alive.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct BoardRepr {
+    width: usize,
+    height: usize,
+    #[serde(default)]
+    edges: EdgeBehavior,
+    #[serde(default, skip_serializing_if = "is_square")]
+    topology: Topology,
+    cells: String,
+}
+
+impl From<Board> for BoardRepr {
+    fn from(board: Board) -> Self {
+        let mut cells = String::new();
+        let mut push_run = |cell: Cell, count: usize| {
+            if count > 1 {
+                cells.push_str(&count.to_string());
+            }
+            cells.push(cell.into());
+        };
+        let mut run: Option<(Cell, usize)> = None;
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                let cell = board[(x, y)];
+                run = match run {
+                    Some((current, count)) if current == cell => Some((current, count + 1)),
+                    Some((current, count)) => {
+                        push_run(current, count);
+                        Some((cell, 1))
+                    }
+                    None => Some((cell, 1)),
+                };
+            }
+        }
+        if let Some((cell, count)) = run {
+            push_run(cell, count);
+        }
+        BoardRepr {
+            width: board.width(),
+            height: board.height(),
+            edges: board.edges(),
+            topology: board.topology(),
+            cells,
+        }
+    }
+}
+
+fn is_square(topology: &Topology) -> bool {
+    *topology == Topology::Square
+}
+
+impl TryFrom<BoardRepr> for Board {
+    type Error = String;
+
+    fn try_from(repr: BoardRepr) -> Result<Self, Self::Error> {
+        if repr.width == 0 || repr.height == 0 {
+            return Err("board cannot be zero sized".to_string());
+        }
+        let expected = repr.width.checked_mul(repr.height).ok_or("board is too large")?;
+        let mut cells = Vec::with_capacity(expected);
+        let mut run = String::new();
+        for c in repr.cells.chars() {
+            if c.is_ascii_digit() {
+                run.push(c);
+                continue;
+            }
+            let cell = Cell::try_from(c).map_err(|_| format!("invalid cell `{}`", c))?;
+            let count = if run.is_empty() { 1 } else { run.parse().map_err(|_| "invalid run length")? };
+            if cells.len() + count > expected {
+                return Err(format!("more than {} cells for a {}x{} board", expected, repr.width, repr.height));
+            }
+            cells.extend(core::iter::repeat_n(cell, count));
+            run.clear();
+        }
+        if !run.is_empty() || cells.len() != expected {
+            return Err(format!("expected {} cells for a {}x{} board, got {}", expected, repr.width, repr.height, cells.len()));
+        }
+        let mut board = Board::new(repr.width, repr.height);
+        board.set_edges(repr.edges);
+        board.set_topology(repr.topology);
+        for (index, cell) in cells.into_iter().enumerate() {
+            board[(index % repr.width, index / repr.width)] = cell;
+        }
+        Ok(board)
+    }
+}
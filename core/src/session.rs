@@ -0,0 +1,93 @@
+use alloc::string::{String, ToString};
+
+use serde::{Deserialize, Serialize};
+
+use crate::board::Board;
+use crate::rules::Rules;
+
+/// Everything needed to resume a run where it was left, not only the cells: the board keeps
+/// its edge behavior, and the front end's view of it is stored alongside.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub board: Board,
+    pub rules: Rules,
+    pub generation: u64,
+    /// Generations per second.
+    pub speed: f64,
+    #[serde(default)]
+    pub paused: bool,
+    /// Theme and display options as named by the front end, e.g. `matrix+ages`.
+    #[serde(default)]
+    pub theme: String,
+}
+
+impl Session {
+    pub fn new(board: Board) -> Self {
+        Session {
+            board,
+            rules: Rules::conway(),
+            generation: 0,
+            speed: 1.0,
+            paused: false,
+            theme: String::new(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("sessions always serialize")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Session {
+    /// Writes the session as JSON to `path`, replacing what was there.
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    pub fn load(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Session::from_json(&json).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{Board, Cell};
+    use crate::grid::EdgeBehavior;
+    use crate::rules::Rules;
+
+    use super::Session;
+
+    #[test]
+    fn round_trip() {
+        let mut board = Board::from_live_coords(5, 4, [(1, 2), (2, 2), (3, 2)]).unwrap();
+        board[(0, 0)] = Cell::Died;
+        board.set_edges(EdgeBehavior::Klein);
+        let session = Session {
+            rules: Rules::highlife(),
+            generation: 42,
+            speed: 15.6,
+            paused: true,
+            theme: "plain+ages".to_string(),
+            ..Session::new(board)
+        };
+        let json = session.to_json();
+        assert!(json.contains("\"B36/S23\""));
+        assert_eq!(Session::from_json(&json), Ok(session.clone()));
+
+        let path = std::env::temp_dir().join(format!("session-{}.json", std::process::id()));
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), session);
+        std::fs::remove_file(&path).unwrap();
+
+        let minimal = r#"{"board":{"width":1,"height":1,"cells":"O"},"rules":"B3/S23","generation":3,"speed":2.0}"#;
+        assert_eq!(Session::from_json(minimal).unwrap().theme, "");
+        assert!(Session::from_json(r#"{"board":{"width":1,"height":1,"cells":"O"},"rules":"B9/S","generation":0,"speed":1.0}"#).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+
+use crate::board::Board;
+use crate::logic::{next_state_masked, next_state_with, resize, Generations};
+use crate::mask::Mask;
+use crate::rules::Rules;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    Toggle {
+        x: usize,
+        y: usize,
+    },
+    Resize {
+        width: usize,
+        height: usize,
+    },
+    Pause,
+    Step,
+    Exit,
+}
+
+type StepHook = Box<dyn FnMut(&Board, u64) + Send>;
+
+/// Board together with its generation counter and pause state, free of any I/O,
+/// so it can be driven by the TUI, an async runtime or a test.
+pub struct Simulation {
+    board: Board,
+    rules: Rules,
+    generation: u64,
+    paused: bool,
+    mask: Option<Mask>,
+    hooks: Vec<StepHook>,
+}
+
+/// Clones start without the hooks of the original.
+impl Clone for Simulation {
+    fn clone(&self) -> Self {
+        Simulation {
+            board: self.board.clone(),
+            rules: self.rules,
+            generation: self.generation,
+            paused: self.paused,
+            mask: self.mask.clone(),
+            hooks: Vec::new(),
+        }
+    }
+}
+
+impl Debug for Simulation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Simulation")
+            .field("board", &self.board)
+            .field("rules", &self.rules)
+            .field("generation", &self.generation)
+            .field("paused", &self.paused)
+            .field("mask", &self.mask)
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
+impl Simulation {
+    pub fn new(board: Board) -> Self {
+        Simulation {
+            board,
+            rules: Rules::conway(),
+            generation: 0,
+            paused: false,
+            mask: None,
+            hooks: Vec::new(),
+        }
+    }
+
+    pub fn board(&self) -> &Board { &self.board }
+    pub fn into_board(self) -> Board { self.board }
+    pub fn rules(&self) -> &Rules { &self.rules }
+    pub fn set_rules(&mut self, rules: Rules) { self.rules = rules }
+    pub fn generation(&self) -> u64 { self.generation }
+    pub fn is_paused(&self) -> bool { self.paused }
+    pub fn mask(&self) -> Option<&Mask> { self.mask.as_ref() }
+
+    /// Only simulates the cells inside `mask`, which is cropped with the board when it is resized.
+    pub fn set_mask(&mut self, mask: Option<Mask>) {
+        if let Some(mask) = &mask {
+            assert_eq!((mask.width(), mask.height()), (self.board.width(), self.board.height()), "mask of another board size");
+        }
+        self.mask = mask;
+    }
+
+    /// Calls `hook` with the board and its generation number after every step, however it
+    /// was triggered. Hooks run in the order they were added, before `step` returns whether
+    /// the board changed, so they also see generations that changed nothing.
+    pub fn on_step(&mut self, hook: impl FnMut(&Board, u64) + Send + 'static) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    pub fn step(&mut self) -> bool {
+        let rules = self.rules.emulated(self.generation);
+        self.generation += 1;
+        let changed = match &self.mask {
+            Some(mask) => next_state_masked(&mut self.board, &rules, mask),
+            None => next_state_with(&mut self.board, &rules),
+        };
+        for hook in &mut self.hooks {
+            hook(&self.board, self.generation);
+        }
+        changed
+    }
+
+    /// Steps unless paused, returns whether the board changed.
+    pub fn tick(&mut self) -> bool {
+        !self.paused && self.step()
+    }
+
+    /// Following generations under the simulation's rules, see `Board::generations`.
+    pub fn generations(self) -> Generations {
+        self.board.generations(self.rules)
+    }
+
+    /// Returns `false` once the simulation was asked to exit.
+    pub fn apply(&mut self, command: Command) -> bool {
+        match command {
+            Command::Toggle { x, y } => {
+                if self.board.check_index((x, y)) {
+                    self.board[(x, y)].flip();
+                }
+            }
+            Command::Resize { width, height } => {
+                resize(&mut self.board, width, height);
+                self.mask = self.mask.take().map(|mask| mask.cropped(width, height));
+            }
+            Command::Pause => self.paused = !self.paused,
+            Command::Step => {
+                self.step();
+            }
+            Command::Exit => return false,
+        }
+        true
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Simulation {
+    /// Ticks every `period` while applying commands as they arrive, calling `on_step`
+    /// after every generation. Returns on `Command::Exit` or when all senders are gone.
+    pub async fn run_async(
+        &mut self,
+        period: core::time::Duration,
+        mut commands: tokio::sync::mpsc::Receiver<Command>,
+        mut on_step: impl FnMut(&Board, u64),
+    ) {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if !self.paused {
+                        self.step();
+                        on_step(&self.board, self.generation);
+                    }
+                }
+                command = commands.recv() => match command {
+                    Some(Command::Step) => {
+                        self.step();
+                        on_step(&self.board, self.generation);
+                    }
+                    Some(command) => {
+                        if !self.apply(command) {
+                            break;
+                        }
+                    }
+                    None => break,
+                }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::board::{Board, Cell};
+
+    use crate::mask::Mask;
+
+    use super::{Command, Simulation};
+
+    fn blinker() -> Board {
+        let mut board = Board::new(5, 5);
+        for x in 1..4 {
+            board[(x, 2)] = Cell::Alive;
+        }
+        board
+    }
+
+    #[test]
+    fn paused_simulation_only_steps_on_command() {
+        let mut simulation = Simulation::new(blinker());
+        assert!(simulation.apply(Command::Pause));
+        assert!(!simulation.tick());
+        assert_eq!(simulation.generation(), 0);
+        simulation.apply(Command::Step);
+        assert_eq!(simulation.generation(), 1);
+        assert!(simulation.board()[(2, 1)].is_alive());
+        assert!(!simulation.apply(Command::Exit));
+    }
+
+    #[test]
+    fn toggle_ignores_out_of_bounds() {
+        let mut simulation = Simulation::new(Board::new(3, 3));
+        simulation.apply(Command::Toggle { x: 1, y: 1 });
+        simulation.apply(Command::Toggle { x: 3, y: 1 });
+        assert_eq!(simulation.board().iter().filter(|entry| entry.cell().is_alive()).count(), 1);
+    }
+
+    #[test]
+    fn hooks_see_every_generation() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let mut simulation = Simulation::new(blinker());
+        let log = seen.clone();
+        simulation.on_step(move |board, generation| log.lock().unwrap().push((generation, board[(2, 1)].is_alive())));
+        simulation.apply(Command::Step);
+        simulation.tick();
+        simulation.apply(Command::Pause);
+        simulation.tick();
+        assert_eq!(*seen.lock().unwrap(), vec![(1, true), (2, false)]);
+        // clones do not share the hooks
+        simulation.clone().step();
+        assert_eq!(seen.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn mask_freezes_outside_cells() {
+        let mut simulation = Simulation::new(blinker());
+        simulation.set_mask(Some(Mask::rect(5, 5, (0, 0), (4, 1))));
+        simulation.step();
+        // only the cell above the blinker is inside the mask
+        assert!(simulation.board()[(2, 1)].is_alive());
+        assert!(simulation.board()[(1, 2)].is_alive() && simulation.board()[(3, 2)].is_alive());
+        assert!(!simulation.board()[(2, 3)].is_alive());
+        simulation.apply(Command::Resize { width: 6, height: 6 });
+        assert_eq!(simulation.mask(), Some(&Mask::rect(6, 6, (0, 0), (4, 1))));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(start_paused = true)]
+    async fn run_async_until_exit() {
+        let (sender, receiver) = tokio::sync::mpsc::channel(8);
+        let mut simulation = Simulation::new(blinker());
+        let mut steps = vec![];
+        let driver = async {
+            tokio::time::sleep(core::time::Duration::from_millis(35)).await;
+            sender.send(Command::Exit).await.unwrap();
+        };
+        let run = simulation.run_async(core::time::Duration::from_millis(10), receiver, |_, generation| steps.push(generation));
+        tokio::join!(run, driver);
+        assert_eq!(steps, vec![1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,88 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::board::Board;
+use crate::logic::detect_cycle;
+use crate::rules::Rules;
+
+/// A soup that lived at least the searched lifespan.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SoupResult {
+    pub seed: u64,
+    /// Generations until the soup settled into still lifes and oscillators, `max_gen` if it never did.
+    pub lifespan: u64,
+    pub final_population: usize,
+}
+
+/// Batch search for long-lived random soups, the same seeds always give the same results.
+#[derive(Clone, Debug)]
+pub struct SoupSearch {
+    pub width: usize,
+    pub height: usize,
+    pub density: f64,
+    pub rules: Rules,
+    /// Generations a soup is run for at most.
+    pub max_gen: u64,
+    /// Longest oscillator period that counts as stabilized.
+    pub max_period: usize,
+    pub min_lifespan: u64,
+}
+
+impl Default for SoupSearch {
+    fn default() -> Self {
+        SoupSearch {
+            width: 32,
+            height: 32,
+            density: 0.35,
+            rules: Rules::conway(),
+            max_gen: 10_000,
+            max_period: 30,
+            min_lifespan: 0,
+        }
+    }
+}
+
+impl SoupSearch {
+    /// Runs the soup of every seed, the ones living at least `min_lifespan` are returned
+    /// longest-lived first.
+    pub fn run(&self, seeds: Range<u64>) -> Vec<SoupResult> {
+        let mut found: Vec<_> = seeds
+            .map(|seed| self.run_soup(seed))
+            .filter(|result| result.lifespan >= self.min_lifespan)
+            .collect();
+        found.sort_by(|a, b| b.lifespan.cmp(&a.lifespan).then(a.seed.cmp(&b.seed)));
+        found
+    }
+
+    pub fn run_soup(&self, seed: u64) -> SoupResult {
+        let mut board = Board::random(self.width, self.height, self.density, seed);
+        let lifespan = detect_cycle(&mut board, &self.rules, self.max_period, self.max_gen)
+            .map_or(self.max_gen, |cycle| cycle.start);
+        SoupResult {
+            seed,
+            lifespan,
+            final_population: board.live_count(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::SoupSearch;
+
+    #[test]
+    fn search_is_sorted_and_reproducible() {
+        let search = SoupSearch { width: 16, height: 16, max_gen: 500, ..SoupSearch::default() };
+        let all = search.run(0..10);
+        assert_eq!(all.len(), 10);
+        assert!(all.windows(2).all(|pair| pair[0].lifespan >= pair[1].lifespan));
+        assert_eq!(all, search.run(0..10));
+
+        let min_lifespan = all[2].lifespan;
+        let long = SoupSearch { min_lifespan, ..search }.run(0..10);
+        assert!(long.len() >= 3);
+        assert!(long.iter().all(|result| result.lifespan >= min_lifespan));
+        assert_eq!(long[..], all[..long.len()]);
+    }
+}
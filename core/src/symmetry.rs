@@ -0,0 +1,86 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+/// Axes a board or an edit is mirrored across.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Symmetry {
+    #[default]
+    None,
+    Vertical,
+    Horizontal,
+    FourFold,
+}
+
+impl Symmetry {
+    pub fn next(self) -> Self {
+        match self {
+            Symmetry::None => Symmetry::Vertical,
+            Symmetry::Vertical => Symmetry::Horizontal,
+            Symmetry::Horizontal => Symmetry::FourFold,
+            Symmetry::FourFold => Symmetry::None,
+        }
+    }
+
+    /// `(x, y)` and its distinct mirror images on a `width` x `height` board.
+    pub fn mirrored(self, (x, y): (usize, usize), width: usize, height: usize) -> Vec<(usize, usize)> {
+        let (mx, my) = (width - 1 - x, height - 1 - y);
+        let mut cells = match self {
+            Symmetry::None => vec![(x, y)],
+            Symmetry::Vertical => vec![(x, y), (mx, y)],
+            Symmetry::Horizontal => vec![(x, y), (x, my)],
+            Symmetry::FourFold => vec![(x, y), (mx, y), (x, my), (mx, my)],
+        };
+        // cells on an axis are their own mirror image and must only flip once
+        cells.sort_unstable();
+        cells.dedup();
+        cells
+    }
+
+    /// Size of the part of a `width` x `height` board the rest is mirrored from, its top left
+    /// corner. Odd sizes keep the middle row or column in it.
+    pub fn fundamental(self, width: usize, height: usize) -> (usize, usize) {
+        let half = |len: usize| len.div_ceil(2);
+        match self {
+            Symmetry::None => (width, height),
+            Symmetry::Vertical => (half(width), height),
+            Symmetry::Horizontal => (width, half(height)),
+            Symmetry::FourFold => (half(width), half(height)),
+        }
+    }
+}
+
+impl Display for Symmetry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Symmetry::None => write!(f, "none"),
+            Symmetry::Vertical => write!(f, "vertical"),
+            Symmetry::Horizontal => write!(f, "horizontal"),
+            Symmetry::FourFold => write!(f, "4-fold"),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Symmetry;
+
+    #[test]
+    fn mirrored_cells() {
+        assert_eq!(Symmetry::None.mirrored((1, 2), 5, 4), vec![(1, 2)]);
+        assert_eq!(Symmetry::Vertical.mirrored((1, 2), 5, 4), vec![(1, 2), (3, 2)]);
+        assert_eq!(Symmetry::Horizontal.mirrored((1, 2), 5, 4), vec![(1, 1), (1, 2)]);
+        assert_eq!(Symmetry::FourFold.mirrored((0, 0), 5, 4), vec![(0, 0), (0, 3), (4, 0), (4, 3)]);
+        // the middle column of an odd width board is its own mirror image
+        assert_eq!(Symmetry::FourFold.mirrored((2, 0), 5, 4), vec![(2, 0), (2, 3)]);
+    }
+
+    #[test]
+    fn fundamental_regions() {
+        assert_eq!(Symmetry::None.fundamental(5, 4), (5, 4));
+        assert_eq!(Symmetry::Vertical.fundamental(5, 4), (3, 4));
+        assert_eq!(Symmetry::FourFold.fundamental(5, 4), (3, 2));
+        assert_eq!(Symmetry::FourFold.fundamental(1, 0), (1, 0));
+    }
+}